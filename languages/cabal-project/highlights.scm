@@ -0,0 +1,16 @@
+; cabal.project files share the field syntax of .cabal files, but stanzas
+; only they have, like `package foo` and `source-repository-package`, aren't
+; part of the grammar. Fields, conditionals and comments are highlighted
+; wherever they parse.
+(comment) @comment
+
+(field_name) @property
+
+(section_name) @type
+
+[
+  (section_type)
+  "if"
+  "elseif"
+  "else"
+] @keyword
@@ -0,0 +1,2 @@
+(_ (section_type)) @indent
+(_ "if") @indent
@@ -0,0 +1,4 @@
+; Stanzas like `library`, `executable foo` and `test-suite spec`.
+(_
+  (section_type) @name
+  (section_name)? @name) @item
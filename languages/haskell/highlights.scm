@@ -12,6 +12,9 @@
 ;; See the License for the specific language governing permissions and
 ;; limitations under the License.
 
+; languages/hsc/highlights.scm is a copy of languages/haskell/highlights.scm;
+; keep the two identical.
+
 ;; ----------------------------------------------------------------------------
 ;; Literals and comments
 
@@ -25,6 +28,14 @@
 
 (comment) @comment
 
+; Haddock: `-- |`, `-- ^`, `{-|`, named chunks and section headings.
+((comment) @comment.doc
+  (#match? @comment.doc "^(--\\s*[|^$*]|\\{-\\s*[|^$*])"))
+
+; Doctest examples, `-- >>> expr`.
+((comment) @preproc
+  (#match? @preproc "^--\\s*>>>"))
+
 
 ;; ----------------------------------------------------------------------------
 ;; Punctuation
@@ -52,7 +63,7 @@
   "∀"
 ] @keyword
 
-(pragma) @constant
+(pragma) @preproc
 
 [
   "if"
@@ -141,16 +152,33 @@
 ;; Types
 
 (type) @type
-(type_variable) @type
+(type_variable) @variable.special
+
+; Kinds, as in `(f :: Type -> Type)`.
+(annotated_type_variable
+  "::"
+  (_) @type.kind)
 
 (constructor) @constructor
 
 ; True or False
 ((constructor) @_bool (#match? @_bool "(True|False)")) @boolean
 
+; Promoted constructors, as in `'True` or `'[]`.
+(promoted) @constructor.promoted
+(promoted (constructor) @constructor.promoted)
+
 
 ;; ----------------------------------------------------------------------------
 ;; Quasi-quotes
 
 (quoter) @function
-; Highlighting of quasiquote_body is handled by injections.scm
+; Bodies in other languages are highlighted by injections.scm
+
+; Raw strings and string interpolation: raw-strings-qq, string-interpolate
+; and neat-interpolation. The body is a single node, so interpolation holes
+; like `#{x}` and `${x}` can't be matched and are highlighted as string too.
+(quasiquote
+  (quoter) @_quoter
+  (#match? @_quoter "^([A-Z][A-Za-z0-9_']*\\.)*(r|i|__i|iii|trimming|untrimming)$")
+  (quasiquote_body) @string)
@@ -1,2 +1,49 @@
 ((comment) @content
   (#set! "language" "comment"))
+
+; SQL quasi-quotes, e.g. postgresql-simple's `[sql| ... |]` and
+; postgresql-typed's `[pgSQL| ... |]`.
+(quasiquote
+  (quoter) @_quoter
+  (#match? @_quoter "^([A-Z][A-Za-z0-9_']*\\.)*(sql|pgSQL|sqlExp)$")
+  (quasiquote_body) @content
+  (#set! "language" "sql"))
+
+; Shakespeare templates, as used by Yesod.
+(quasiquote
+  (quoter) @_quoter
+  (#match? @_quoter "^([A-Z][A-Za-z0-9_']*\\.)*(hamlet|shamlet|whamlet|ihamlet|xhamlet)$")
+  (quasiquote_body) @content
+  (#set! "language" "html"))
+
+(quasiquote
+  (quoter) @_quoter
+  (#match? @_quoter "^([A-Z][A-Za-z0-9_']*\\.)*(lucius|cassius)$")
+  (quasiquote_body) @content
+  (#set! "language" "css"))
+
+(quasiquote
+  (quoter) @_quoter
+  (#match? @_quoter "^([A-Z][A-Za-z0-9_']*\\.)*julius$")
+  (quasiquote_body) @content
+  (#set! "language" "javascript"))
+
+; Embedded configuration: Dhall, YAML from yaml's `yamlQQ`, and JSON from
+; aeson-qq's `aesonQQ`.
+(quasiquote
+  (quoter) @_quoter
+  (#match? @_quoter "^([A-Z][A-Za-z0-9_']*\\.)*dhall$")
+  (quasiquote_body) @content
+  (#set! "language" "dhall"))
+
+(quasiquote
+  (quoter) @_quoter
+  (#match? @_quoter "^([A-Z][A-Za-z0-9_']*\\.)*yamlQQ$")
+  (quasiquote_body) @content
+  (#set! "language" "yaml"))
+
+(quasiquote
+  (quoter) @_quoter
+  (#match? @_quoter "^([A-Z][A-Za-z0-9_']*\\.)*aesonQQ$")
+  (quasiquote_body) @content
+  (#set! "language" "json"))
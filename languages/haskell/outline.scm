@@ -10,9 +10,38 @@
   "newtype" @context
   name: (type) @name) @item
 
+(type_family
+  "type" @context
+  "family" @context
+  .
+  (type) @name) @item
+
+(data_family
+  "data" @context
+  "family" @context
+  .
+  (type) @name) @item
+
+(type_instance
+  "type" @context
+  "instance" @context
+  .
+  (type) @name) @item
+
+(data_instance
+  "data" @context
+  "instance" @context
+  .
+  (type) @name) @item
+
 (signature
   name: (variable) @name) @item
 
+; Default implementations of class methods.
+(class_body
+  (function
+    name: (variable) @name) @item)
+
 (class
   "class" @context
   (class_head) @name) @item
@@ -23,4 +52,14 @@
 
 (foreign_import
   "foreign" @context
-  (impent) @name) @item
+  "import" @context
+  (callconv) @context
+  (signature
+    name: (variable) @name)) @item
+
+(foreign_export
+  "foreign" @context
+  "export" @context
+  (callconv) @context
+  (signature
+    name: (variable) @name)) @item
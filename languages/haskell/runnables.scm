@@ -0,0 +1,99 @@
+; `main`, the entry point of executables.
+(
+  (function
+    name: (variable) @run
+    (#eq? @run "main"))
+  (#set! tag haskell-main)
+)
+
+; hspec-discover specs, `spec :: Spec`.
+(
+  (function
+    name: (variable) @run
+    (#eq? @run "spec"))
+  (#set! tag haskell-hspec)
+)
+
+; tasty test trees, `tests :: TestTree`.
+(
+  (signature
+    name: (variable) @run
+    (type) @_type
+    (#eq? @_type "TestTree"))
+  (#set! tag haskell-tasty)
+)
+
+; QuickCheck properties, `prop_reverse xs = ...`. hspec and tasty select
+; tests by their description, which is given where the property is used,
+; as in `it "reverses twice" prop_reverse`, so this runs the whole suite.
+(
+  (function
+    name: (variable) @run
+    (#match? @run "^prop_"))
+  (#set! tag haskell-property)
+)
+
+; A single hspec or sydtest item, `it "reverses lists" $ ...`. Both take
+; the description as --match, quotes included. Queries can't see which
+; framework a module imports, so the test frameworks are told apart by the
+; functions they define tests with.
+(
+  (exp_apply
+    .
+    (exp_name
+      (variable) @run @_function)
+    .
+    (exp_literal
+      (string) @hspec_item)
+    (#match? @_function "^(describe|context|it|specify|prop)$"))
+  (#set! tag haskell-hspec-item)
+)
+
+; A single tasty test, `testCase "adds numbers" $ ...`. Groups are left
+; out: the name includes its quotes, which only fit a pattern comparing it
+; with the last component of the test path.
+(
+  (exp_apply
+    .
+    (exp_name
+      (variable) @run @_function)
+    .
+    (exp_literal
+      (string) @tasty_test)
+    (#match? @_function "^(testCase|testProperty|testSpec|goldenVsFile|goldenVsString)$"))
+  (#set! tag haskell-tasty-test)
+)
+
+; Doctest examples, `-- >>> reverse [1, 2, 3]`.
+(
+  (comment) @run
+  (#match? @run "^--\\s*>>>")
+  (#set! tag haskell-doctest)
+)
+
+; criterion and tasty-bench benchmarks, `bench "reverse" $ nf reverse xs`.
+(
+  (exp_apply
+    .
+    (exp_name
+      (variable) @run @_function)
+    .
+    (exp_literal
+      (string) @benchmark)
+    (#eq? @_function "bench"))
+  (#set! tag haskell-benchmark)
+)
+
+; Benchmark groups, `bgroup "lists" [...]`, which only criterion can select
+; by name.
+(
+  (exp_apply
+    .
+    (exp_name
+      (variable) @run @_function)
+    .
+    (exp_literal
+      (string) @benchmark)
+    (#eq? @_function "bgroup"))
+  (#set! tag haskell-benchmark-group)
+)
@@ -8,5 +8,13 @@
 
 (record_fields "{" (_)* @class.inside "}")
 
+[
+  (class)
+  (instance)
+] @class.around
+
+(class_body (where) . (_)* @class.inside)
+(instance (where) . (_)* @class.inside)
+
 ((signature)? (function)+) @function.around
 (function rhs:(_) @function.inside)
@@ -0,0 +1,184 @@
+;; Copyright 2022 nvim-treesitter
+;;
+;; Licensed under the Apache License, Version 2.0 (the "License");
+;; you may not use this file except in compliance with the License.
+;; You may obtain a copy of the License at
+;;
+;;     http://www.apache.org/licenses/LICENSE-2.0
+;;
+;; Unless required by applicable law or agreed to in writing, software
+;; distributed under the License is distributed on an "AS IS" BASIS,
+;; WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+;; See the License for the specific language governing permissions and
+;; limitations under the License.
+
+; languages/hsc/highlights.scm is a copy of languages/haskell/highlights.scm;
+; keep the two identical.
+
+;; ----------------------------------------------------------------------------
+;; Literals and comments
+
+(integer) @number
+(exp_negation) @number
+(exp_literal (float)) @float
+(char) @string
+(string) @string
+
+(con_unit) @symbol  ; unit, as in ()
+
+(comment) @comment
+
+; Haddock: `-- |`, `-- ^`, `{-|`, named chunks and section headings.
+((comment) @comment.doc
+  (#match? @comment.doc "^(--\\s*[|^$*]|\\{-\\s*[|^$*])"))
+
+; Doctest examples, `-- >>> expr`.
+((comment) @preproc
+  (#match? @preproc "^--\\s*>>>"))
+
+
+;; ----------------------------------------------------------------------------
+;; Punctuation
+
+[
+  "("
+  ")"
+  "{"
+  "}"
+  "["
+  "]"
+] @punctuation.bracket
+
+[
+  (comma)
+  ";"
+] @punctuation.delimiter
+
+
+;; ----------------------------------------------------------------------------
+;; Keywords, operators, includes
+
+[
+  "forall"
+  "∀"
+] @keyword
+
+(pragma) @preproc
+
+[
+  "if"
+  "then"
+  "else"
+  "case"
+  "of"
+] @keyword
+
+(exp_lambda_cases "\\" ("cases" @variant))
+
+[
+  "import"
+  "qualified"
+  "module"
+] @keyword
+
+[
+  (operator)
+  (constructor_operator)
+  (type_operator)
+  (tycon_arrow)
+  (qualified_module)  ; grabs the `.` (dot), ex: import System.IO
+  (all_names)
+  (wildcard)
+  "="
+  "|"
+  "::"
+  "=>"
+  "->"
+  "<-"
+  "\\"
+  "`"
+  "@"
+] @operator
+
+(module) @title
+
+[
+  (where)
+  "let"
+  "in"
+  "class"
+  "instance"
+  "data"
+  "newtype"
+  "family"
+  "type"
+  "as"
+  "hiding"
+  "deriving"
+  "via"
+  "stock"
+  "anyclass"
+  "do"
+  "mdo"
+  "rec"
+  "infix"
+  "infixl"
+  "infixr"
+] @keyword
+
+
+;; ----------------------------------------------------------------------------
+;; Functions and variables
+
+(variable) @variable
+(pat_wildcard) @variable
+
+(signature name: (variable) @type)
+(function
+  name: (variable) @function
+  patterns: (patterns))
+((signature (fun)) . (function (variable) @function))
+((signature (context (fun))) . (function (variable) @function))
+((signature (forall (context (fun)))) . (function (variable) @function))
+
+(exp_infix (variable) @operator)  ; consider infix functions as operators
+
+(exp_infix (exp_name) @function (#set! "priority" 101))
+(exp_apply . (exp_name (variable) @function))
+(exp_apply . (exp_name (qualified_variable (variable) @function)))
+
+
+;; ----------------------------------------------------------------------------
+;; Types
+
+(type) @type
+(type_variable) @variable.special
+
+; Kinds, as in `(f :: Type -> Type)`.
+(annotated_type_variable
+  "::"
+  (_) @type.kind)
+
+(constructor) @constructor
+
+; True or False
+((constructor) @_bool (#match? @_bool "(True|False)")) @boolean
+
+; Promoted constructors, as in `'True` or `'[]`.
+(promoted) @constructor.promoted
+(promoted (constructor) @constructor.promoted)
+
+
+;; ----------------------------------------------------------------------------
+;; Quasi-quotes
+
+(quoter) @function
+; Bodies in other languages are highlighted by injections.scm
+
+; Raw strings and string interpolation: raw-strings-qq, string-interpolate
+; and neat-interpolation. The body is a single node, so interpolation holes
+; like `#{x}` and `${x}` can't be matched and are highlighted as string too.
+(quasiquote
+  (quoter) @_quoter
+  (#match? @_quoter "^([A-Z][A-Za-z0-9_']*\\.)*(r|i|__i|iii|trimming|untrimming)$")
+  (quasiquote_body) @string)
@@ -0,0 +1,18 @@
+; languages/hsc/indents.scm is a copy of languages/haskell/indents.scm; keep
+; the two identical.
+
+(_ "[" "]" @end) @indent
+(_ "{" "}" @end) @indent
+(_ "(" ")" @end) @indent
+
+; Layout blocks: the lines after `do`, `of`, `\case` and `let`, after the
+; `=` or guards of a binding, and in `where` blocks are indented.
+(exp_do) @indent
+(exp_case) @indent
+(exp_lambda_case) @indent
+(exp_let) @indent
+(exp_let_in
+  "in" @end) @indent
+(function) @indent
+(class) @indent
+(instance) @indent
@@ -0,0 +1,9 @@
+((comment) @content
+  (#set! "language" "comment"))
+
+; hsc2hs directives like `#include` and `#define` are C preprocessor lines.
+; `#{size ...}` and `#{peek ...}` inside expressions aren't: the grammar
+; parses them as broken Haskell, with no node covering the whole directive
+; that could be injected as C.
+((cpp) @content
+  (#set! "language" "c"))
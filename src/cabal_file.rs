@@ -0,0 +1,608 @@
+use std::collections::BTreeSet;
+
+use zed_extension_api::process::Command;
+use zed_extension_api::{self as zed, Result};
+
+/// Reads a package's `.cabal` file from the worktree root, returning its
+/// file name and contents.
+///
+/// `argument` is the file a slash command was given, with or without the
+/// `.cabal` extension. Without one, the file is taken to be named after the
+/// worktree's directory, which clones like `foo-main/` don't match.
+pub fn read(worktree: &zed::Worktree, argument: Option<&str>) -> Result<(String, String)> {
+    let file_name = match argument {
+        Some(argument) => format!("{}.cabal", argument.trim_end_matches(".cabal")),
+        None => {
+            let root_path = worktree.root_path();
+            let directory = root_path.rsplit(['/', '\\']).next().unwrap_or_default();
+            format!("{directory}.cabal")
+        }
+    };
+    let contents = worktree.read_text_file(&file_name).map_err(|_| {
+        format!(
+            "found no {file_name} in the worktree root, \
+             pass the package's .cabal file as an argument, e.g. `my-package.cabal`"
+        )
+    })?;
+    Ok((file_name, contents))
+}
+
+/// Returns the version of the GHC on the worktree's `PATH`, e.g. `[9, 8, 2]`.
+pub fn ghc_version(worktree: &zed::Worktree) -> Option<Vec<u64>> {
+    worktree.which("ghc")?;
+    let output = Command::new("ghc")
+        .arg("--numeric-version")
+        .envs(worktree.shell_env())
+        .output()
+        .ok()?;
+    if output.status != Some(0) {
+        return None;
+    }
+    parse_version(String::from_utf8_lossy(&output.stdout).trim())
+}
+
+/// Returns the package names from every `build-depends` field of a `.cabal`
+/// file, excluding the package's own internal libraries.
+///
+/// Conditional blocks are evaluated against `ghc`. Conditions that can't be
+/// decided, such as flags or a missing compiler version, include both
+/// branches.
+pub fn build_depends(contents: &str, ghc: Option<&[u64]>) -> Vec<String> {
+    let mut package_name = None;
+    let mut dependencies = BTreeSet::new();
+    let mut field_indent = None;
+    let mut conditionals = Conditionals::default();
+
+    for line in contents.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with("--") {
+            continue;
+        }
+        let indent = line.len() - line.trim_start().len();
+        let lowercase = trimmed.to_ascii_lowercase();
+
+        if field_indent.is_some_and(|field_indent| indent > field_indent) {
+            if conditionals.active() {
+                insert_dependencies(&mut dependencies, trimmed);
+            }
+            continue;
+        }
+        field_indent = None;
+
+        if conditionals.handle_line(indent, trimmed, ghc) {
+            continue;
+        }
+        if let Some(value) = lowercase.strip_prefix("build-depends:") {
+            field_indent = Some(indent);
+            if conditionals.active() {
+                insert_dependencies(&mut dependencies, &trimmed[trimmed.len() - value.len()..]);
+            }
+        } else if let Some(name) = lowercase.strip_prefix("name:") {
+            if indent == 0 {
+                package_name = Some(trimmed[trimmed.len() - name.len()..].trim().to_string());
+            }
+        }
+    }
+
+    if let Some(package_name) = package_name {
+        dependencies.remove(&package_name);
+    }
+    dependencies.into_iter().collect()
+}
+
+/// A library, executable, test suite or benchmark of a `.cabal` file.
+#[derive(Debug, PartialEq)]
+pub struct Component {
+    /// The component as a cabal target, e.g. `demo:exe:demo`.
+    pub target: String,
+    pub main_is: Option<String>,
+    pub source_dirs: Vec<String>,
+}
+
+/// Returns the components of a `.cabal` file.
+///
+/// Conditional blocks are evaluated against `ghc` like in [`build_depends`].
+/// Fields in blocks that may apply are all read, so the first `main-is`
+/// wins.
+pub fn components(contents: &str, ghc: Option<&[u64]>) -> Vec<Component> {
+    let mut package_name = String::new();
+    let mut components: Vec<Component> = Vec::new();
+    let mut in_component = false;
+    let mut in_source_dirs = None;
+    let mut conditionals = Conditionals::default();
+
+    for line in contents.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with("--") {
+            continue;
+        }
+        let indent = line.len() - line.trim_start().len();
+        let lowercase = trimmed.to_ascii_lowercase();
+
+        if indent == 0 {
+            in_component = false;
+            in_source_dirs = None;
+            conditionals = Conditionals::default();
+            if let Some(name) = lowercase.strip_prefix("name:") {
+                package_name = trimmed[trimmed.len() - name.len()..].trim().to_string();
+            }
+            let mut words = trimmed.split_whitespace();
+            let kind = match words
+                .next()
+                .unwrap_or_default()
+                .to_ascii_lowercase()
+                .as_str()
+            {
+                "library" => "lib",
+                "executable" => "exe",
+                "test-suite" => "test",
+                "benchmark" => "bench",
+                _ => continue,
+            };
+            // Only the main library has no name.
+            let name = words.next().unwrap_or(&package_name);
+            components.push(Component {
+                target: format!("{package_name}:{kind}:{name}"),
+                main_is: None,
+                source_dirs: Vec::new(),
+            });
+            in_component = true;
+            continue;
+        }
+        let Some(component) = components.last_mut().filter(|_| in_component) else {
+            continue;
+        };
+
+        if in_source_dirs.is_some_and(|field_indent| indent > field_indent) {
+            if conditionals.active() {
+                insert_source_dirs(&mut component.source_dirs, trimmed);
+            }
+            continue;
+        }
+        in_source_dirs = None;
+
+        if conditionals.handle_line(indent, trimmed, ghc) || !conditionals.active() {
+            continue;
+        }
+        if let Some(value) = lowercase.strip_prefix("hs-source-dirs:") {
+            in_source_dirs = Some(indent);
+            insert_source_dirs(
+                &mut component.source_dirs,
+                &trimmed[trimmed.len() - value.len()..],
+            );
+        } else if let Some(value) = lowercase.strip_prefix("main-is:") {
+            component
+                .main_is
+                .get_or_insert_with(|| trimmed[trimmed.len() - value.len()..].trim().to_string());
+        }
+    }
+
+    for component in &mut components {
+        if component.source_dirs.is_empty() {
+            component.source_dirs.push(".".to_string());
+        }
+    }
+    components
+}
+
+fn insert_source_dirs(source_dirs: &mut Vec<String>, value: &str) {
+    for dir in value.split([',', ' ', '\t']).filter(|dir| !dir.is_empty()) {
+        if !source_dirs.iter().any(|existing| existing == dir) {
+            source_dirs.push(dir.to_string());
+        }
+    }
+}
+
+fn insert_dependencies(dependencies: &mut BTreeSet<String>, value: &str) {
+    for dep in value.split(',') {
+        let Some(name) = dep.split_whitespace().next() else {
+            continue;
+        };
+        let name = name.split([':', '^', '<', '>', '=']).next().unwrap_or(name);
+        if !name.is_empty() {
+            dependencies.insert(name.to_string());
+        }
+    }
+}
+
+/// Tracks the `if`/`elif`/`else` blocks enclosing the current line.
+#[derive(Default)]
+struct Conditionals {
+    /// `(indent, whether the block applies)` for every enclosing block.
+    open: Vec<(usize, Option<bool>)>,
+    /// `(indent, whether any branch was taken)` for every `if`/`elif` chain
+    /// that a following `elif` or `else` could continue.
+    chains: Vec<(usize, Option<bool>)>,
+}
+
+impl Conditionals {
+    /// Whether the current line is inside blocks that (may) apply.
+    fn active(&self) -> bool {
+        self.open.iter().all(|(_, applies)| *applies != Some(false))
+    }
+
+    /// Updates the open blocks for a line, returning whether the line was
+    /// a conditional itself.
+    fn handle_line(&mut self, indent: usize, line: &str, ghc: Option<&[u64]>) -> bool {
+        while let Some(&(block_indent, _)) = self.open.last() {
+            if block_indent < indent {
+                break;
+            }
+            self.open.pop();
+        }
+        self.chains
+            .retain(|(chain_indent, _)| *chain_indent <= indent);
+        let previous = match self.chains.last() {
+            Some(&(chain_indent, taken)) if chain_indent == indent => {
+                self.chains.pop();
+                Some(taken)
+            }
+            _ => None,
+        };
+
+        let lowercase = line.to_ascii_lowercase();
+        let (applies, taken) = if let Some(condition) = lowercase.strip_prefix("if ") {
+            let applies = evaluate_condition(condition, ghc);
+            (applies, applies)
+        } else if let (Some(condition), Some(taken)) = (lowercase.strip_prefix("elif "), previous) {
+            let applies = match taken {
+                Some(true) => Some(false),
+                Some(false) => evaluate_condition(condition, ghc),
+                None => None,
+            };
+            (applies, or(taken, applies))
+        } else if let (true, Some(taken)) = (lowercase == "else", previous) {
+            (taken.map(|taken| !taken), Some(true))
+        } else {
+            return false;
+        };
+
+        self.open.push((indent, applies));
+        self.chains.push((indent, taken));
+        true
+    }
+}
+
+/// Evaluates a cabal condition such as `impl(ghc >= 9.4) && !os(windows)`.
+fn evaluate_condition(condition: &str, ghc: Option<&[u64]>) -> Option<bool> {
+    let tokens = tokenize_condition(condition);
+    let mut pos = 0;
+    let result = parse_or(&tokens, &mut pos, ghc);
+    if pos == tokens.len() {
+        result
+    } else {
+        None
+    }
+}
+
+fn tokenize_condition(condition: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut chars = condition.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '(' | ')' | '!' => tokens.push(c.to_string()),
+            '&' | '|' => {
+                chars.next_if_eq(&c);
+                tokens.push(format!("{c}{c}"));
+            }
+            _ => {
+                let mut word = c.to_string();
+                while let Some(c) = chars.next_if(|c| c.is_alphanumeric() || *c == '-' || *c == '_')
+                {
+                    word.push(c);
+                }
+                // Keep the arguments of `impl(...)`, `flag(...)` etc. together.
+                if chars.peek() == Some(&'(') {
+                    let mut depth = 0;
+                    for c in chars.by_ref() {
+                        word.push(c);
+                        match c {
+                            '(' => depth += 1,
+                            ')' => depth -= 1,
+                            _ => {}
+                        }
+                        if depth == 0 {
+                            break;
+                        }
+                    }
+                }
+                tokens.push(word);
+            }
+        }
+    }
+    tokens
+}
+
+fn parse_or(tokens: &[String], pos: &mut usize, ghc: Option<&[u64]>) -> Option<bool> {
+    let mut result = parse_and(tokens, pos, ghc);
+    while tokens.get(*pos).is_some_and(|t| t == "||") {
+        *pos += 1;
+        result = or(result, parse_and(tokens, pos, ghc));
+    }
+    result
+}
+
+fn parse_and(tokens: &[String], pos: &mut usize, ghc: Option<&[u64]>) -> Option<bool> {
+    let mut result = parse_not(tokens, pos, ghc);
+    while tokens.get(*pos).is_some_and(|t| t == "&&") {
+        *pos += 1;
+        result = and(result, parse_not(tokens, pos, ghc));
+    }
+    result
+}
+
+fn parse_not(tokens: &[String], pos: &mut usize, ghc: Option<&[u64]>) -> Option<bool> {
+    let token = tokens.get(*pos)?;
+    *pos += 1;
+    match token.as_str() {
+        "!" => parse_not(tokens, pos, ghc).map(|value| !value),
+        "(" => {
+            let result = parse_or(tokens, pos, ghc);
+            if tokens.get(*pos).is_some_and(|t| t == ")") {
+                *pos += 1;
+            }
+            result
+        }
+        "true" => Some(true),
+        "false" => Some(false),
+        token => {
+            let arguments = token
+                .strip_prefix("impl(")
+                .and_then(|arguments| arguments.strip_suffix(')'))?;
+            let (compiler, range) = arguments
+                .trim()
+                .split_once(char::is_whitespace)
+                .unwrap_or((arguments.trim(), ""));
+            if compiler != "ghc" {
+                return Some(false);
+            }
+            version_in_range(ghc?, range)
+        }
+    }
+}
+
+fn and(a: Option<bool>, b: Option<bool>) -> Option<bool> {
+    match (a, b) {
+        (Some(false), _) | (_, Some(false)) => Some(false),
+        (Some(true), Some(true)) => Some(true),
+        _ => None,
+    }
+}
+
+fn or(a: Option<bool>, b: Option<bool>) -> Option<bool> {
+    match (a, b) {
+        (Some(true), _) | (_, Some(true)) => Some(true),
+        (Some(false), Some(false)) => Some(false),
+        _ => None,
+    }
+}
+
+/// Evaluates a version range such as `>= 9.2 && < 9.8` or `^>= 9.4`.
+fn version_in_range(version: &[u64], range: &str) -> Option<bool> {
+    let range = range.trim();
+    if range.is_empty() || range == "-any" {
+        return Some(true);
+    }
+    if let Some((a, b)) = range.split_once("||") {
+        return Some(version_in_range(version, a)? || version_in_range(version, b)?);
+    }
+    if let Some((a, b)) = range.split_once("&&") {
+        return Some(version_in_range(version, a)? && version_in_range(version, b)?);
+    }
+    let range = range.trim_matches(|c| c == '(' || c == ')').trim();
+
+    let operator_end = range
+        .find(|c: char| c.is_ascii_digit())
+        .unwrap_or(range.len());
+    let (operator, bound) = range.split_at(operator_end);
+    let operator = operator.trim();
+    if let Some(prefix) = bound.strip_suffix(".*") {
+        let prefix = parse_version(prefix)?;
+        let matches = version.starts_with(&prefix);
+        return match operator {
+            "==" => Some(matches),
+            _ => None,
+        };
+    }
+    let bound = parse_version(bound.trim())?;
+    let ordering = compare(version, &bound);
+    match operator {
+        ">=" => Some(ordering.is_ge()),
+        ">" => Some(ordering.is_gt()),
+        "<=" => Some(ordering.is_le()),
+        "<" => Some(ordering.is_lt()),
+        "==" => Some(ordering.is_eq()),
+        "^>=" => {
+            // `^>= 9.4.1` means `>= 9.4.1 && < 9.5`.
+            let mut upper = bound.iter().take(2).copied().collect::<Vec<_>>();
+            upper.resize(2, 0);
+            upper[1] += 1;
+            Some(ordering.is_ge() && compare(version, &upper).is_lt())
+        }
+        _ => None,
+    }
+}
+
+/// Compares versions, treating missing components as zero.
+fn compare(a: &[u64], b: &[u64]) -> std::cmp::Ordering {
+    let len = a.len().max(b.len());
+    let component = |v: &[u64], i: usize| v.get(i).copied().unwrap_or(0);
+    (0..len)
+        .map(|i| component(a, i).cmp(&component(b, i)))
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(std::cmp::Ordering::Equal)
+}
+
+fn parse_version(version: &str) -> Option<Vec<u64>> {
+    version.split('.').map(|part| part.parse().ok()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CABAL_FILE: &str = "\
+cabal-version: 3.0
+name:          demo
+version:       0.1.0.0
+
+flag dev
+  default: False
+
+library
+  build-depends:  base >=4.14 && <5, text ^>=2.0
+                , containers
+  if impl(ghc >= 9.6)
+    build-depends: ghc-experimental
+  elif impl(ghc >= 9.2) && !os(windows)
+    build-depends: unix
+  else
+    build-depends: ghc-compat
+  if flag(dev)
+    build-depends: pretty-simple
+  exposed-modules: Demo
+
+executable demo
+  main-is: Main.hs
+  hs-source-dirs: app
+  Build-Depends:
+      base
+    , demo
+  if impl(ghc < 9.4 || >= 9.10)
+    build-depends: old-time
+
+test-suite spec
+  type:           exitcode-stdio-1.0
+  hs-source-dirs: test
+                , shared
+  main-is:        Spec.hs
+";
+
+    #[test]
+    fn test_build_depends_evaluates_conditionals() {
+        assert_eq!(
+            build_depends(CABAL_FILE, Some(&[9, 8, 2])),
+            vec![
+                "base",
+                "containers",
+                "ghc-experimental",
+                "pretty-simple",
+                "text"
+            ]
+        );
+        assert_eq!(
+            build_depends(CABAL_FILE, Some(&[9, 4, 8])),
+            vec![
+                "base",
+                "containers",
+                "ghc-compat",
+                "pretty-simple",
+                "text",
+                "unix"
+            ]
+        );
+        assert_eq!(
+            build_depends(CABAL_FILE, Some(&[9, 0, 2])),
+            vec![
+                "base",
+                "containers",
+                "ghc-compat",
+                "old-time",
+                "pretty-simple",
+                "text"
+            ]
+        );
+        assert_eq!(
+            build_depends(CABAL_FILE, None),
+            vec![
+                "base",
+                "containers",
+                "ghc-compat",
+                "ghc-experimental",
+                "old-time",
+                "pretty-simple",
+                "text",
+                "unix"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_build_depends_excludes_mixed_case_package() {
+        assert_eq!(
+            build_depends(
+                "name: MyLib\nlibrary\n  build-depends: base, text\n\
+                 executable my-app\n  build-depends: base, MyLib\n",
+                None
+            ),
+            vec!["base", "text"]
+        );
+    }
+
+    #[test]
+    fn test_components() {
+        assert_eq!(
+            components(CABAL_FILE, None),
+            vec![
+                Component {
+                    target: "demo:lib:demo".to_string(),
+                    main_is: None,
+                    source_dirs: vec![".".to_string()],
+                },
+                Component {
+                    target: "demo:exe:demo".to_string(),
+                    main_is: Some("Main.hs".to_string()),
+                    source_dirs: vec!["app".to_string()],
+                },
+                Component {
+                    target: "demo:test:spec".to_string(),
+                    main_is: Some("Spec.hs".to_string()),
+                    source_dirs: vec!["test".to_string(), "shared".to_string()],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_components_evaluate_conditionals() {
+        let cabal_file = "\
+name: demo
+
+executable demo
+  if impl(ghc >= 9.6)
+    main-is: Main96.hs
+    hs-source-dirs: app-9.6
+  else
+    main-is: Main.hs
+  hs-source-dirs: app
+";
+        let main_is = |ghc: Option<&[u64]>| {
+            let components = components(cabal_file, ghc);
+            (
+                components[0].main_is.clone(),
+                components[0].source_dirs.clone(),
+            )
+        };
+
+        assert_eq!(
+            main_is(Some(&[9, 8, 2])),
+            (
+                Some("Main96.hs".to_string()),
+                vec!["app-9.6".to_string(), "app".to_string()]
+            )
+        );
+        assert_eq!(
+            main_is(Some(&[9, 4, 8])),
+            (Some("Main.hs".to_string()), vec!["app".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_version_in_range() {
+        assert_eq!(version_in_range(&[9, 4, 8], "^>= 9.4.2"), Some(true));
+        assert_eq!(version_in_range(&[9, 6, 1], "^>= 9.4.2"), Some(false));
+        assert_eq!(version_in_range(&[9, 6, 1], "== 9.6.*"), Some(true));
+        assert_eq!(version_in_range(&[9, 6], ">= 9.6.0"), Some(true));
+    }
+}
@@ -0,0 +1,165 @@
+use zed_extension_api::process::Command;
+use zed_extension_api::{self as zed, Result};
+
+/// Log messages of `haskell-language-server-wrapper <file>` describing how
+/// it resolved the cradle.
+const FACTS: &[&str] = &[
+    "Cradle type",
+    "Cradle directory",
+    "Project GHC version",
+    "No 'hie.yaml' found",
+    "ghc:",
+    "cabal:",
+    "stack:",
+];
+
+/// The output of checking a file the way HLS loads it.
+pub struct Diagnosis {
+    pub hls_output: String,
+    /// The GHC flags of the file's component, if hie-bios is installed.
+    pub flags: Option<String>,
+}
+
+/// Runs `haskell-language-server-wrapper <file>` in the worktree root, which
+/// loads the file's component through its cradle, along with
+/// `hie-bios flags <file>` if hie-bios is installed.
+pub fn diagnose(worktree: &zed::Worktree, hls: &str, file: &str) -> Result<Diagnosis> {
+    let root_path = worktree.root_path();
+    // Commands can't be given a working directory, so HLS is told to change
+    // into the worktree, and hie-bios finds the cradle from the file's path.
+    let hls_output = combined_output(
+        crate::hls_command(hls, worktree.shell_env())?.args(["--cwd", &root_path, file]),
+    )?;
+    let flags = match worktree.which("hie-bios") {
+        Some(_) => Some(combined_output(
+            Command::new("hie-bios")
+                .args(["flags", &format!("{root_path}/{file}")])
+                .envs(worktree.shell_env()),
+        )?),
+        None => None,
+    };
+    Ok(Diagnosis { hls_output, flags })
+}
+
+/// Runs `command`, returning its output and errors whether or not it
+/// succeeded.
+fn combined_output(mut command: Command) -> Result<String> {
+    let output = command.output()?;
+    Ok(format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    ))
+}
+
+/// Returns the cradle facts and the errors from the output of
+/// `haskell-language-server-wrapper <file>`, without log prefixes.
+pub fn summary(output: &str) -> (Vec<String>, Vec<String>) {
+    let mut facts = Vec::new();
+    let mut errors = Vec::new();
+    for line in output.lines().map(strip_log_prefix) {
+        let line = line.trim();
+        if FACTS.iter().any(|fact| line.starts_with(fact)) {
+            facts.push(line.to_string());
+        } else if line.to_ascii_lowercase().contains("error") || line.starts_with("Failed") {
+            errors.push(line.to_string());
+        }
+    }
+    (facts, errors)
+}
+
+/// Strips the `2024-06-01T12:00:00.000Z | Info | ` prefix of HLS log lines.
+fn strip_log_prefix(line: &str) -> &str {
+    if !line.starts_with(|c: char| c.is_ascii_digit()) {
+        return line;
+    }
+    let mut parts = line.splitn(3, " | ");
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(_), Some(_), Some(message)) => message,
+        _ => line,
+    }
+}
+
+/// Renders a diagnosis as Markdown, returning the text and the range and
+/// label of every part.
+pub fn render(
+    file: &str,
+    diagnosis: &Diagnosis,
+) -> (String, Vec<(std::ops::Range<usize>, String)>) {
+    let (facts, errors) = summary(&diagnosis.hls_output);
+    let mut text = String::new();
+    let mut sections = Vec::new();
+    let mut section = |text: &mut String, label: &str, body: String| {
+        let start = text.len();
+        text.push_str(&body);
+        text.push('\n');
+        sections.push((start..text.len(), label.to_string()));
+    };
+
+    let cradle = if facts.is_empty() {
+        "HLS didn't report how it resolved the cradle.\n".to_string()
+    } else {
+        facts.iter().map(|fact| format!("- {fact}\n")).collect()
+    };
+    section(&mut text, &format!("Cradle for {file}"), cradle);
+    let errors = if errors.is_empty() {
+        format!("HLS loaded {file} without errors.\n")
+    } else {
+        format!("```\n{}\n```\n", errors.join("\n"))
+    };
+    section(&mut text, "Errors", errors);
+    if let Some(flags) = &diagnosis.flags {
+        section(
+            &mut text,
+            "GHC flags (hie-bios)",
+            format!("```\n{}\n```\n", flags.trim_end()),
+        );
+    }
+    section(
+        &mut text,
+        "haskell-language-server-wrapper output",
+        format!("```\n{}\n```\n", diagnosis.hls_output.trim_end()),
+    );
+    (text, sections)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary() {
+        let output = "\
+No 'hie.yaml' found. Try to discover the project type!
+Run entered for haskell-language-server-wrapper(haskell-language-server-wrapper) Version 2.9.0.0
+Cradle directory: /src/demo
+Cradle type: Cabal
+Tool versions found on the $PATH
+cabal:\t\t3.10.3.0
+ghc:\t\t9.8.2
+2024-06-01T12:00:00.000Z | Info | Project GHC version: 9.8.2
+2024-06-01T12:00:01.000Z | Error | Failed to parse result of calling cabal
+src/Demo.hs:3:8: error: Could not find module 'Data.Text'
+";
+        let (facts, errors) = summary(output);
+
+        assert_eq!(
+            facts,
+            vec![
+                "No 'hie.yaml' found. Try to discover the project type!",
+                "Cradle directory: /src/demo",
+                "Cradle type: Cabal",
+                "cabal:\t\t3.10.3.0",
+                "ghc:\t\t9.8.2",
+                "Project GHC version: 9.8.2",
+            ]
+        );
+        assert_eq!(
+            errors,
+            vec![
+                "Failed to parse result of calling cabal",
+                "src/Demo.hs:3:8: error: Could not find module 'Data.Text'",
+            ]
+        );
+    }
+}
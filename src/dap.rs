@@ -0,0 +1,378 @@
+use std::fs;
+
+use zed_extension_api::process::Command;
+use zed_extension_api::{self as zed, Result};
+
+use crate::{cabal_file, extension_log};
+
+/// The debug adapter, as named in `extension.toml` and debug scenarios.
+pub const ADAPTER_NAME: &str = "haskell-debug-adapter";
+
+/// The debug locator, as named in `extension.toml`.
+pub const LOCATOR_NAME: &str = "haskell";
+
+/// Set by hspec tasks to the file of the spec item, which `cabal test` and
+/// `stack test` don't name.
+const SOURCE_FILE_VAR: &str = "HASKELL_SOURCE_FILE";
+
+/// The GHCi prompt haskell-debug-adapter expects while debugging.
+const GHCI_PROMPT: &str = "H>>= ";
+
+/// The debug adapter and the GHCi it drives.
+pub struct Binaries {
+    pub adapter: String,
+    pub ghci_dap: String,
+}
+
+/// Finds haskell-debug-adapter and ghci-dap on the worktree's `PATH`, or
+/// installs them with cabal into the extension's directory.
+///
+/// ghci-dap has to be built with the project's GHC, so installations are
+/// kept per GHC version and reused by every project using that version.
+pub fn find_or_install(worktree: &zed::Worktree) -> Result<Binaries> {
+    if let (Some(adapter), Some(_)) = (worktree.which(ADAPTER_NAME), worktree.which("ghci-dap")) {
+        return Ok(Binaries {
+            adapter,
+            ghci_dap: "ghci-dap".to_string(),
+        });
+    }
+
+    let version = cabal_file::ghc_version(worktree)
+        .ok_or("haskell-debug-adapter is not installed, and GHC is needed to install it")?;
+    let version = version
+        .iter()
+        .map(u64::to_string)
+        .collect::<Vec<_>>()
+        .join(".");
+    let install_dir = std::env::current_dir()
+        .map_err(|e| e.to_string())?
+        .join(format!("dap-ghc-{version}"));
+    let suffix = match zed::current_platform() {
+        (zed::Os::Windows, _) => ".exe",
+        _ => "",
+    };
+    let binaries = Binaries {
+        adapter: install_dir
+            .join(format!("{ADAPTER_NAME}{suffix}"))
+            .to_string_lossy()
+            .into_owned(),
+        ghci_dap: install_dir
+            .join(format!("ghci-dap{suffix}"))
+            .to_string_lossy()
+            .into_owned(),
+    };
+    if fs::metadata(&binaries.adapter).is_ok() && fs::metadata(&binaries.ghci_dap).is_ok() {
+        return Ok(binaries);
+    }
+
+    if worktree.which("cabal").is_none() {
+        return Err(
+            "haskell-debug-adapter is not installed, and cabal is needed to install it".into(),
+        );
+    }
+    extension_log::log(format!(
+        "installing haskell-debug-adapter and ghci-dap for GHC {version} into {}",
+        install_dir.display()
+    ));
+    let output = Command::new("cabal")
+        .args([
+            "install",
+            ADAPTER_NAME,
+            "ghci-dap",
+            "--install-method=copy",
+            "--overwrite-policy=always",
+        ])
+        .arg(format!("--installdir={}", install_dir.display()))
+        .envs(worktree.shell_env())
+        .output()?;
+    if output.status != Some(0) {
+        return Err(format!(
+            "failed to install haskell-debug-adapter: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    Ok(binaries)
+}
+
+/// Returns the command haskell-debug-adapter starts `ghci_dap` with, for a
+/// cabal or a Stack project, loading the given cabal target if any.
+pub fn ghci_command(stack_project: bool, ghci_dap: &str, target: Option<&str>) -> String {
+    match (stack_project, target) {
+        (true, Some(target)) if target.ends_with(".hs") => format!(
+            "stack ghci {target} --with-ghc={ghci_dap} --test --no-load --no-build"
+        ),
+        (true, Some(target)) => format!(
+            "stack ghci {target} --with-ghc={ghci_dap} --test --no-load --no-build --main-is {target}"
+        ),
+        (true, None) => format!("stack ghci --with-ghc={ghci_dap} --test --no-load --no-build"),
+        (false, target) => format!(
+            "cabal repl {}--with-compiler={ghci_dap} --repl-no-load --builddir=dist-newstyle/dap",
+            target.map(|target| format!("{target} ")).unwrap_or_default()
+        ),
+    }
+}
+
+/// Returns a debug scenario for every executable and test suite with a
+/// `main-is`, to be added to `.zed/debug.json`. `exists` tells whether a worktree-relative
+/// path exists, to find the source directory containing the entry point.
+pub fn scenarios(
+    components: &[cabal_file::Component],
+    exists: impl Fn(&str) -> bool,
+) -> serde_json::Value {
+    let scenarios = components
+        .iter()
+        .filter(|component| {
+            component.target.contains(":exe:") || component.target.contains(":test:")
+        })
+        .filter_map(|component| {
+            let main_is = component.main_is.as_deref()?;
+            let startup = component
+                .source_dirs
+                .iter()
+                .map(|dir| match dir.as_str() {
+                    "." => main_is.to_string(),
+                    dir => format!("{}/{main_is}", dir.trim_end_matches('/')),
+                })
+                .find(|path| exists(path))
+                .unwrap_or_else(|| main_is.to_string());
+            let kind = if component.target.contains(":test:") {
+                "test-suite"
+            } else {
+                "executable"
+            };
+            let name = component.target.rsplit(':').next().unwrap_or_default();
+            Some(serde_json::json!({
+                "label": format!("Debug {kind} {name}"),
+                "adapter": ADAPTER_NAME,
+                "request": "launch",
+                "target": component.target,
+                "startup": startup
+            }))
+        })
+        .collect();
+    serde_json::Value::Array(scenarios)
+}
+
+/// Returns the haskell-debug-adapter configuration debugging what a task
+/// from `tasks.json` runs: the `main` of a `cabal run` file, or the `spec`
+/// of an hspec item's file. Other tasks, such as profiling runs, can't be
+/// debugged.
+pub fn locate(
+    command: &str,
+    args: &[String],
+    env: &[(String, String)],
+) -> Option<serde_json::Value> {
+    let program = command.rsplit(['/', '\\']).next().unwrap_or(command);
+    if !matches!(program, "cabal" | "stack") {
+        return None;
+    }
+    let (subcommand, args) = args.split_first()?;
+    match subcommand.as_str() {
+        "run" => {
+            let (own_args, main_args) = match args.iter().position(|arg| arg == "--") {
+                Some(separator) => (&args[..separator], &args[separator + 1..]),
+                None => (args, &[][..]),
+            };
+            if own_args.iter().any(|arg| arg == "--enable-profiling") {
+                return None;
+            }
+            let startup = own_args.iter().find(|arg| arg.ends_with(".hs"))?;
+            let mut config = launch_config(startup, main_args, false);
+            config["target"] = startup.as_str().into();
+            Some(config)
+        }
+        "test" => {
+            args.iter().find(|arg| {
+                arg.starts_with("--test-options=--match ")
+                    || arg.starts_with("--test-arguments=--match ")
+            })?;
+            let (_, startup) = env.iter().find(|(name, _)| name == SOURCE_FILE_VAR)?;
+            let mut config = launch_config(startup, &[], false);
+            config["target"] = startup.as_str().into();
+            config["startupFunc"] = "hspec".into();
+            config["startupArgs"] = "spec".into();
+            Some(config)
+        }
+        _ => None,
+    }
+}
+
+/// Fills in the parts of a haskell-debug-adapter configuration that users
+/// rarely want to change.
+pub fn with_defaults(
+    mut config: serde_json::Value,
+    workspace: &str,
+    stack_project: bool,
+    ghci_dap: &str,
+) -> serde_json::Value {
+    let Some(object) = config.as_object_mut() else {
+        return config;
+    };
+    let target = object.get("target").and_then(|target| target.as_str());
+    let ghci_cmd = ghci_command(stack_project, ghci_dap, target);
+    let defaults = [
+        ("request", serde_json::json!("launch")),
+        ("workspace", serde_json::json!(workspace)),
+        ("startupFunc", serde_json::json!("")),
+        ("startupArgs", serde_json::json!("")),
+        ("stopOnEntry", serde_json::json!(false)),
+        ("mainArgs", serde_json::json!("")),
+        ("ghciPrompt", serde_json::json!(GHCI_PROMPT)),
+        ("ghciInitialPrompt", serde_json::json!("> ")),
+        ("ghciCmd", serde_json::json!(ghci_cmd)),
+        ("ghciEnv", serde_json::json!({})),
+        (
+            "logFile",
+            serde_json::json!(format!("{workspace}/.zed/hda.log")),
+        ),
+        ("logLevel", serde_json::json!("WARNING")),
+        ("forceInspect", serde_json::json!(false)),
+    ];
+    for (key, value) in defaults {
+        object.entry(key).or_insert(value);
+    }
+    // haskell-debug-adapter needs an absolute `startup` path; leave Zed
+    // variables like `$ZED_FILE` and Windows paths alone.
+    if let Some(serde_json::Value::String(startup)) = object.get_mut("startup") {
+        if !startup.starts_with(['/', '$']) && !startup.contains(':') {
+            *startup = format!("{workspace}/{startup}");
+        }
+    }
+    config
+}
+
+/// Returns the haskell-debug-adapter configuration that runs `main` of the
+/// `startup` module with `args`.
+pub fn launch_config(startup: &str, args: &[String], stop_on_entry: bool) -> serde_json::Value {
+    serde_json::json!({
+        "request": "launch",
+        "startup": startup,
+        "mainArgs": args.join(" "),
+        "stopOnEntry": stop_on_entry
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_defaults() {
+        let config = launch_config("app/Main.hs", &["--verbose".to_string()], true);
+        let config = with_defaults(config, "/src/project", false, "ghci-dap");
+
+        assert_eq!(config["startup"], "/src/project/app/Main.hs");
+        assert_eq!(config["mainArgs"], "--verbose");
+        assert_eq!(config["stopOnEntry"], true);
+        assert_eq!(config["workspace"], "/src/project");
+        assert_eq!(config["ghciPrompt"], GHCI_PROMPT);
+        assert_eq!(
+            config["ghciCmd"],
+            "cabal repl --with-compiler=ghci-dap --repl-no-load --builddir=dist-newstyle/dap"
+        );
+        assert_eq!(config["logFile"], "/src/project/.zed/hda.log");
+    }
+
+    #[test]
+    fn test_with_defaults_keeps_user_values() {
+        let config = serde_json::json!({
+            "startup": "test/Spec.hs",
+            "ghciCmd": "cabal repl spec --with-compiler=ghci-dap"
+        });
+        let config = with_defaults(config, "/src/project", true, "/dap/ghci-dap");
+
+        assert_eq!(
+            config["ghciCmd"],
+            "cabal repl spec --with-compiler=ghci-dap"
+        );
+        assert_eq!(config["request"], "launch");
+    }
+
+    #[test]
+    fn test_with_defaults_loads_target() {
+        let config = serde_json::json!({
+            "startup": "test/Spec.hs",
+            "target": "demo:test:spec"
+        });
+        let config = with_defaults(config, "/src/project", true, "/dap/ghci-dap");
+
+        assert_eq!(
+            config["ghciCmd"],
+            "stack ghci demo:test:spec --with-ghc=/dap/ghci-dap --test --no-load --no-build --main-is demo:test:spec"
+        );
+    }
+
+    #[test]
+    fn test_locate() {
+        let args = |args: &[&str]| args.iter().map(ToString::to_string).collect::<Vec<_>>();
+
+        let config = locate(
+            "cabal",
+            &args(&["run", "app/Main.hs", "--", "-v", "input.txt"]),
+            &[],
+        )
+        .unwrap();
+        assert_eq!(config["startup"], "app/Main.hs");
+        assert_eq!(config["target"], "app/Main.hs");
+        assert_eq!(config["mainArgs"], "-v input.txt");
+
+        let env = [(
+            SOURCE_FILE_VAR.to_string(),
+            "test/ParserSpec.hs".to_string(),
+        )];
+        let config = locate(
+            "/usr/bin/stack",
+            &args(&["test", "--test-arguments=--match \"parses\""]),
+            &env,
+        )
+        .unwrap();
+        assert_eq!(config["startup"], "test/ParserSpec.hs");
+        assert_eq!(config["startupFunc"], "hspec");
+
+        assert_eq!(
+            locate(
+                "cabal",
+                &args(&["run", "--enable-profiling", "app/Main.hs"]),
+                &[]
+            ),
+            None
+        );
+        assert_eq!(locate("stack", &args(&["run"]), &[]), None);
+        assert_eq!(locate("cabal", &args(&["test"]), &env), None);
+        assert_eq!(locate("hlint", &args(&["run", "app/Main.hs"]), &[]), None);
+    }
+
+    #[test]
+    fn test_scenarios() {
+        let components = cabal_file::components(
+            "name: demo\n\
+             library\n  hs-source-dirs: src\n\
+             executable demo\n  main-is: Main.hs\n  hs-source-dirs: src, app\n\
+             test-suite spec\n  main-is: Spec.hs\n\
+             benchmark bench\n  main-is: Bench.hs\n\
+             executable script\n  hs-source-dirs: scripts\n",
+            None,
+        );
+        let scenarios = scenarios(&components, |path| path == "app/Main.hs");
+
+        assert_eq!(
+            scenarios,
+            serde_json::json!([
+                {
+                    "label": "Debug executable demo",
+                    "adapter": ADAPTER_NAME,
+                    "request": "launch",
+                    "target": "demo:exe:demo",
+                    "startup": "app/Main.hs"
+                },
+                {
+                    "label": "Debug test-suite spec",
+                    "adapter": ADAPTER_NAME,
+                    "request": "launch",
+                    "target": "demo:test:spec",
+                    "startup": "Spec.hs"
+                }
+            ])
+        );
+    }
+}
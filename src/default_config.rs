@@ -0,0 +1,116 @@
+/// Returns whether `output` is what `generate-default-config` prints, as
+/// opposed to a `vscode-extension-schema` dump, whose keys are all dotted
+/// `haskell.*` paths.
+pub fn is_default_config(output: &serde_json::Value) -> bool {
+    output
+        .as_object()
+        .is_some_and(|object| !object.keys().any(|key| key.starts_with("haskell.")))
+}
+
+/// Synthesizes a `vscode-extension-schema` style schema from the output of
+/// `generate-default-config`, for HLS versions that predate the former.
+///
+/// The default config only has values, so each setting gets its default
+/// and a type inferred from it, but no description.
+pub fn to_raw_schema(config: &serde_json::Value) -> serde_json::Value {
+    // Some versions nest the settings under `haskell`, others don't.
+    let config = config.get("haskell").unwrap_or(config);
+    let mut schema = serde_json::Map::new();
+    add_settings(&mut schema, "haskell", config);
+    serde_json::Value::Object(schema)
+}
+
+fn add_settings(
+    schema: &mut serde_json::Map<String, serde_json::Value>,
+    path: &str,
+    value: &serde_json::Value,
+) {
+    if let Some(object) = value.as_object() {
+        for (key, value) in object {
+            add_settings(schema, &format!("{path}.{key}"), value);
+        }
+        return;
+    }
+    let ty = match value {
+        serde_json::Value::Bool(_) => "boolean",
+        serde_json::Value::String(_) => "string",
+        serde_json::Value::Number(number) if number.is_f64() => "number",
+        serde_json::Value::Number(_) => "integer",
+        serde_json::Value::Array(_) => "array",
+        _ => return,
+    };
+    schema.insert(
+        path.to_string(),
+        serde_json::json!({
+            "default": value,
+            "type": ty
+        }),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_raw_schema() {
+        let config = serde_json::json!({
+            "haskell": {
+                "checkParents": "CheckOnSave",
+                "maxCompletions": 40,
+                "plugin": {
+                    "hlint": {
+                        "config": { "flags": [] },
+                        "globalOn": true
+                    }
+                }
+            }
+        });
+        assert!(is_default_config(&config));
+
+        let schema = to_raw_schema(&config);
+        assert!(!is_default_config(&schema));
+        assert_eq!(
+            schema,
+            serde_json::json!({
+                "haskell.checkParents": {
+                    "default": "CheckOnSave",
+                    "type": "string"
+                },
+                "haskell.maxCompletions": {
+                    "default": 40,
+                    "type": "integer"
+                },
+                "haskell.plugin.hlint.config.flags": {
+                    "default": [],
+                    "type": "array"
+                },
+                "haskell.plugin.hlint.globalOn": {
+                    "default": true,
+                    "type": "boolean"
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_to_raw_schema_without_haskell_key() {
+        let config = serde_json::json!({
+            "formattingProvider": "ormolu",
+            "plugin": { "eval": { "globalOn": true } }
+        });
+        assert_eq!(
+            to_raw_schema(&config),
+            serde_json::json!({
+                "haskell.formattingProvider": {
+                    "default": "ormolu",
+                    "type": "string"
+                },
+                "haskell.plugin.eval.globalOn": {
+                    "default": true,
+                    "type": "boolean"
+                }
+            })
+        );
+    }
+}
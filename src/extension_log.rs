@@ -0,0 +1,27 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+/// How many entries to keep before dropping the oldest.
+const MAX_ENTRIES: usize = 200;
+
+static ENTRIES: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+/// Records a decision the extension made, so `/haskell-extension-log` can
+/// explain it later. Entries also go to Zed's log.
+pub fn log(message: String) {
+    eprintln!("haskell: {message}");
+    let Ok(mut entries) = ENTRIES.lock() else {
+        return;
+    };
+    if entries.len() == MAX_ENTRIES {
+        entries.pop_front();
+    }
+    entries.push_back(message);
+}
+
+pub fn entries() -> Vec<String> {
+    ENTRIES
+        .lock()
+        .map(|entries| entries.iter().cloned().collect())
+        .unwrap_or_default()
+}
@@ -0,0 +1,135 @@
+use std::collections::BTreeSet;
+
+use crate::formatter::Order;
+
+/// The key under `lsp.hls.settings` holding the extension's own settings.
+/// It is removed before the settings are sent to HLS.
+pub const KEY: &str = "extension";
+
+/// Experimental parts of the extension, which are off unless enabled under
+/// `features`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Feature {
+    Dap,
+    FallbackDiagnostics,
+}
+
+impl Feature {
+    pub const ALL: [Feature; 2] = [Feature::Dap, Feature::FallbackDiagnostics];
+
+    /// The key the feature is enabled with.
+    pub fn name(self) -> &'static str {
+        match self {
+            Feature::Dap => "dap",
+            Feature::FallbackDiagnostics => "fallback-diagnostics",
+        }
+    }
+}
+
+/// Settings for the extension itself, as opposed to HLS.
+///
+/// These are read per worktree, so a project's `.zed/settings.json` can
+/// override them.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ExtensionSettings {
+    /// Refuse to pass settings HLS doesn't know about to it, instead of
+    /// letting HLS silently ignore them.
+    pub strict: bool,
+    pub features: BTreeSet<Feature>,
+    /// Run stylish-haskell before or after the main formatter, e.g. to
+    /// align imports.
+    pub stylish_haskell: Option<Order>,
+}
+
+impl ExtensionSettings {
+    /// Reads the extension settings from the `lsp.hls.settings` of a
+    /// worktree. Missing or malformed settings fall back to the defaults.
+    pub fn from_lsp_settings(settings: Option<&serde_json::Value>) -> Self {
+        let Some(settings) = settings.and_then(|settings| settings.get(KEY)) else {
+            return Self::default();
+        };
+        let features = settings.get("features").and_then(|f| f.as_object());
+        Self {
+            strict: settings
+                .get("strict")
+                .and_then(|strict| strict.as_bool())
+                .unwrap_or_default(),
+            features: Feature::ALL
+                .into_iter()
+                .filter(|feature| {
+                    features
+                        .and_then(|features| features.get(feature.name()))
+                        .and_then(|enabled| enabled.as_bool())
+                        .unwrap_or_default()
+                })
+                .collect(),
+            stylish_haskell: settings
+                .get("stylish-haskell")
+                .and_then(|order| order.as_str())
+                .and_then(Order::from_name),
+        }
+    }
+
+    pub fn enabled(&self, feature: Feature) -> bool {
+        self.features.contains(&feature)
+    }
+}
+
+/// Removes the extension settings from `settings`, leaving what HLS
+/// should see.
+pub fn strip(settings: &mut serde_json::Value) {
+    if let Some(settings) = settings.as_object_mut() {
+        settings.remove(KEY);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extension_settings() {
+        let mut settings = serde_json::json!({
+            "extension": { "strict": true, "stylish-haskell": "after" },
+            "haskell": { "maxCompletions": 20 }
+        });
+        assert_eq!(
+            ExtensionSettings::from_lsp_settings(Some(&settings)),
+            ExtensionSettings {
+                strict: true,
+                features: BTreeSet::new(),
+                stylish_haskell: Some(Order::After),
+            }
+        );
+
+        strip(&mut settings);
+        assert_eq!(
+            settings,
+            serde_json::json!({ "haskell": { "maxCompletions": 20 } })
+        );
+        assert_eq!(
+            ExtensionSettings::from_lsp_settings(Some(&settings)),
+            ExtensionSettings::default()
+        );
+        assert_eq!(
+            ExtensionSettings::from_lsp_settings(None),
+            ExtensionSettings::default()
+        );
+    }
+
+    #[test]
+    fn test_feature_flags() {
+        let settings = serde_json::json!({
+            "extension": {
+                "features": {
+                    "dap": true,
+                    "fallback-diagnostics": "yes",
+                    "telepathy": true
+                }
+            }
+        });
+        let settings = ExtensionSettings::from_lsp_settings(Some(&settings));
+        assert!(settings.enabled(Feature::Dap));
+        assert!(!settings.enabled(Feature::FallbackDiagnostics));
+    }
+}
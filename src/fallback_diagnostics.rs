@@ -0,0 +1,97 @@
+use std::fs;
+
+use zed_extension_api::{self as zed, Result};
+
+use crate::{extension_log, formatter};
+
+/// Configures efm-langserver to report hlint's hints for saved Haskell files,
+/// and to format them with `formatter` if given.
+///
+/// hlint's `--json` output has a line per hint. efm reads a severity from a
+/// single letter, so every hlint severity gets a format matching the letter
+/// of the LSP one it maps to: `Error` to `E`, `Warning` to `W` and
+/// `Suggestion` to the `i` of information, like HLS's hlint plugin does.
+fn efm_config(formatter: Option<&str>) -> String {
+    let mut config = "\
+version: 2
+languages:
+  haskell:
+    - lint-command: 'hlint --json --no-exit-code ${INPUT}'
+      lint-stdin: false
+      lint-after-open: true
+      lint-source: 'hlint'
+      lint-formats:
+        - '%.%#\"severity\":\"%trror\",\"hint\":\"%m\",\"file\":\"%f\",\"startLine\":%l,\"startColumn\":%c,\"endLine\":%e,\"endColumn\":%k,%.%#'
+        - '%.%#\"severity\":\"%tarning\",\"hint\":\"%m\",\"file\":\"%f\",\"startLine\":%l,\"startColumn\":%c,\"endLine\":%e,\"endColumn\":%k,%.%#'
+        - '%.%#\"severity\":\"Suggest%tion\",\"hint\":\"%m\",\"file\":\"%f\",\"startLine\":%l,\"startColumn\":%c,\"endLine\":%e,\"endColumn\":%k,%.%#'
+"
+    .to_string();
+    if let Some(command) =
+        formatter.and_then(|provider| formatter::external_command(provider, "${INPUT}"))
+    {
+        config.push_str(&format!(
+            "    - format-command: '{command}'\n      format-stdin: true\n"
+        ));
+    }
+    config
+}
+
+/// Returns the command starting efm-langserver to run hlint, and `formatter`
+/// if it is installed, for worktrees where HLS can't be found.
+pub fn hlint_command(
+    worktree: &zed::Worktree,
+    env: Vec<(String, String)>,
+    formatter: Option<&str>,
+) -> Result<zed::Command> {
+    let efm = worktree.which("efm-langserver").ok_or(
+        "hls must be installed via ghcup; without it, hlint diagnostics need efm-langserver",
+    )?;
+    if worktree.which("hlint").is_none() {
+        return Err("hls must be installed via ghcup; without it, diagnostics need hlint".into());
+    }
+    let formatter = formatter.filter(|provider| {
+        let installed = formatter::external_command_installed(worktree, provider);
+        if !installed {
+            extension_log::log(format!(
+                "{provider} isn't installed, so files aren't formatted without HLS"
+            ));
+        }
+        installed
+    });
+    let config = std::env::current_dir()
+        .map_err(|e| e.to_string())?
+        .join("hlint-efm.yaml");
+    fs::write(&config, efm_config(formatter))
+        .map_err(|e| format!("failed to write {config:?}: {e}"))?;
+    extension_log::log(format!(
+        "HLS not found for {}, reporting hlint hints with {efm} instead",
+        worktree.root_path()
+    ));
+    Ok(zed::Command {
+        command: efm,
+        args: vec!["-c".to_string(), config.to_string_lossy().into_owned()],
+        env,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_efm_config_maps_hlint_severities() {
+        let config = efm_config(None);
+        assert!(config.contains("hlint --json --no-exit-code ${INPUT}"));
+        for severity in ["%trror", "%tarning", "Suggest%tion"] {
+            assert!(config.contains(&format!("\"severity\":\"{severity}\"")));
+        }
+    }
+
+    #[test]
+    fn test_efm_config_formats_with_provider() {
+        assert!(!efm_config(None).contains("format-command"));
+        assert!(!efm_config(Some("none")).contains("format-command"));
+        assert!(efm_config(Some("ormolu"))
+            .ends_with("    - format-command: 'ormolu --stdin-input-file ${INPUT}'\n      format-stdin: true\n"));
+    }
+}
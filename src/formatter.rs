@@ -0,0 +1,318 @@
+use std::fs;
+use std::path::PathBuf;
+
+use zed_extension_api::process::Command;
+use zed_extension_api::{self as zed, Result};
+
+use crate::extension_log;
+
+/// Formatting providers HLS can be configured with, along with the command
+/// Zed can run for them when HLS was built without the plugin.
+const EXTERNAL_FORMATTERS: &[(&str, &str, &[&str])] = &[
+    ("ormolu", "ormolu", &["--stdin-input-file", "{buffer_path}"]),
+    (
+        "fourmolu",
+        "fourmolu",
+        &["--stdin-input-file", "{buffer_path}"],
+    ),
+    ("stylish-haskell", "stylish-haskell", &[]),
+    ("floskell", "floskell", &[]),
+];
+
+/// Returns the configured `formattingProvider`, if any.
+pub fn configured_provider(haskell_settings: &serde_json::Value) -> Option<&str> {
+    haskell_settings.get("formattingProvider")?.as_str()
+}
+
+/// Returns whether the HLS plugin for `provider` is turned off in the
+/// (expanded) `haskell_settings`, so HLS won't format with it.
+pub fn plugin_disabled(haskell_settings: &serde_json::Value, provider: &str) -> bool {
+    haskell_settings["plugin"][provider]["globalOn"] == false
+}
+
+/// Returns the shell command formatting stdin with `provider`, where
+/// `input` stands for the path of the formatted file.
+pub fn external_command(provider: &str, input: &str) -> Option<String> {
+    let (_, command, arguments) = EXTERNAL_FORMATTERS
+        .iter()
+        .find(|(name, _, _)| *name == provider)?;
+    let mut command = command.to_string();
+    for argument in *arguments {
+        command.push(' ');
+        command.push_str(&argument.replace("{buffer_path}", input));
+    }
+    Some(command)
+}
+
+/// Returns whether the command Zed can run for `provider` is on the
+/// worktree's `PATH`.
+pub fn external_command_installed(worktree: &zed::Worktree, provider: &str) -> bool {
+    EXTERNAL_FORMATTERS
+        .iter()
+        .find(|(name, _, _)| *name == provider)
+        .is_some_and(|(_, command, _)| worktree.which(command).is_some())
+}
+
+/// Returns whether the HLS the converted `schema` came from can format with
+/// `provider`. When the schema doesn't tell, HLS is assumed to support it.
+pub fn hls_supports(schema: &serde_json::Value, provider: &str) -> bool {
+    if provider == "none" {
+        return true;
+    }
+    let properties = &schema["properties"];
+    if let Some(allowed) = properties["formattingProvider"]["enum"].as_array() {
+        return allowed.iter().any(|allowed| allowed == provider);
+    }
+    match properties["plugin"]["properties"].as_object() {
+        Some(plugins) => plugins.contains_key(provider),
+        None => true,
+    }
+}
+
+/// Returns whether the HLS the converted `schema` came from can format
+/// `.cabal` files with cabal-fmt.
+pub fn hls_supports_cabal_fmt(schema: &serde_json::Value) -> bool {
+    let properties = &schema["properties"];
+    if let Some(allowed) = properties["cabalFormattingProvider"]["enum"].as_array() {
+        return allowed.iter().any(|allowed| allowed == "cabal-fmt");
+    }
+    properties["plugin"]["properties"]["cabal-fmt"].is_object()
+}
+
+/// Returns where `install_cabal_fmt` puts cabal-fmt: its directory and the
+/// path of the binary.
+fn cabal_fmt_install_path() -> Result<(PathBuf, String)> {
+    let install_dir = std::env::current_dir()
+        .map_err(|e| e.to_string())?
+        .join("cabal-fmt");
+    let suffix = match zed::current_platform() {
+        (zed::Os::Windows, _) => ".exe",
+        _ => "",
+    };
+    let cabal_fmt = install_dir
+        .join(format!("cabal-fmt{suffix}"))
+        .to_string_lossy()
+        .into_owned();
+    Ok((install_dir, cabal_fmt))
+}
+
+/// Returns the path of cabal-fmt if `install_cabal_fmt` installed it.
+pub fn installed_cabal_fmt() -> Option<String> {
+    let (_, cabal_fmt) = cabal_fmt_install_path().ok()?;
+    fs::metadata(&cabal_fmt).is_ok().then_some(cabal_fmt)
+}
+
+/// Installs cabal-fmt with cabal into the extension's directory, returning
+/// its path. An earlier installation is reused.
+pub fn install_cabal_fmt(worktree: &zed::Worktree) -> Result<String> {
+    if let Some(cabal_fmt) = installed_cabal_fmt() {
+        return Ok(cabal_fmt);
+    }
+    let (install_dir, cabal_fmt) = cabal_fmt_install_path()?;
+
+    if worktree.which("cabal").is_none() {
+        return Err("cabal-fmt is not installed, and cabal is needed to install it".into());
+    }
+    extension_log::log(format!(
+        "installing cabal-fmt into {}",
+        install_dir.display()
+    ));
+    let output = Command::new("cabal")
+        .args(["install", "cabal-fmt", "--install-method=copy"])
+        .arg(format!("--installdir={}", install_dir.display()))
+        .envs(worktree.shell_env())
+        .output()?;
+    if output.status != Some(0) {
+        return Err(format!(
+            "failed to install cabal-fmt: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    Ok(cabal_fmt)
+}
+
+/// Returns the Zed language settings that format `.cabal` files with
+/// cabal-fmt, going through HLS when it can, or running `cabal_fmt` directly.
+pub fn cabal_language_settings(cabal_fmt: &str, through_hls: bool) -> serde_json::Value {
+    if through_hls {
+        return serde_json::json!({ "formatter": "language_server" });
+    }
+    serde_json::json!({
+        "formatter": {
+            "external": {
+                "command": cabal_fmt,
+                "arguments": []
+            }
+        }
+    })
+}
+
+/// When stylish-haskell runs relative to the main formatter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+    Before,
+    After,
+}
+
+impl Order {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "before" => Some(Order::Before),
+            "after" => Some(Order::After),
+            _ => None,
+        }
+    }
+}
+
+/// Returns the Zed language settings that format Haskell with `provider`,
+/// going through HLS when it supports the provider, and with
+/// stylish-haskell before or after it if requested.
+pub fn zed_language_settings(
+    provider: &str,
+    hls_supports_provider: bool,
+    stylish_haskell: Option<Order>,
+) -> serde_json::Value {
+    let formatter = (provider != "none").then(|| {
+        if hls_supports_provider {
+            serde_json::json!("language_server")
+        } else {
+            external_formatter(provider).unwrap_or_else(|| serde_json::json!("language_server"))
+        }
+    });
+    let stylish_haskell = stylish_haskell
+        .filter(|_| provider != "stylish-haskell")
+        .zip(external_formatter("stylish-haskell"));
+    match (formatter, stylish_haskell) {
+        (None, None) => serde_json::json!({ "format_on_save": "off" }),
+        (Some(formatter), None) | (None, Some((_, formatter))) => {
+            serde_json::json!({ "formatter": formatter })
+        }
+        (Some(formatter), Some((Order::Before, stylish_haskell))) => {
+            serde_json::json!({ "formatter": [stylish_haskell, formatter] })
+        }
+        (Some(formatter), Some((Order::After, stylish_haskell))) => {
+            serde_json::json!({ "formatter": [formatter, stylish_haskell] })
+        }
+    }
+}
+
+/// Returns the Zed formatter running `provider` directly.
+fn external_formatter(provider: &str) -> Option<serde_json::Value> {
+    let (_, command, arguments) = EXTERNAL_FORMATTERS
+        .iter()
+        .find(|(name, _, _)| *name == provider)?;
+    Some(serde_json::json!({
+        "external": {
+            "command": command,
+            "arguments": arguments
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_external_formatter_fallback() {
+        let schema = crate::convert_to_zed_schema(&serde_json::json!({
+            "haskell.formattingProvider": {
+                "default": "ormolu",
+                "type": "string"
+            },
+            "haskell.plugin.ormolu.config.external": {
+                "default": false,
+                "type": "boolean"
+            }
+        }));
+
+        assert!(hls_supports(&schema, "ormolu"));
+        assert!(!hls_supports(&schema, "fourmolu"));
+
+        assert_eq!(
+            zed_language_settings("ormolu", true, None),
+            serde_json::json!({ "formatter": "language_server" })
+        );
+        assert_eq!(
+            zed_language_settings("fourmolu", false, None),
+            serde_json::json!({
+                "formatter": {
+                    "external": {
+                        "command": "fourmolu",
+                        "arguments": ["--stdin-input-file", "{buffer_path}"]
+                    }
+                }
+            })
+        );
+        assert_eq!(
+            zed_language_settings("none", true, None),
+            serde_json::json!({ "format_on_save": "off" })
+        );
+    }
+
+    #[test]
+    fn test_stylish_haskell_as_second_formatter() {
+        let stylish_haskell = serde_json::json!({
+            "external": { "command": "stylish-haskell", "arguments": [] }
+        });
+
+        assert_eq!(
+            zed_language_settings("fourmolu", true, Some(Order::Before)),
+            serde_json::json!({ "formatter": [stylish_haskell, "language_server"] })
+        );
+        assert_eq!(
+            zed_language_settings("fourmolu", true, Some(Order::After)),
+            serde_json::json!({ "formatter": ["language_server", stylish_haskell] })
+        );
+        assert_eq!(
+            zed_language_settings("none", true, Some(Order::After)),
+            serde_json::json!({ "formatter": stylish_haskell })
+        );
+        assert_eq!(
+            zed_language_settings("stylish-haskell", true, Some(Order::After)),
+            serde_json::json!({ "formatter": "language_server" })
+        );
+    }
+
+    #[test]
+    fn test_cabal_fmt() {
+        let schema = crate::convert_to_zed_schema(&serde_json::json!({
+            "haskell.cabalFormattingProvider": {
+                "default": "cabal-gild",
+                "enum": ["cabal-gild", "cabal-fmt", "none"],
+                "type": "string"
+            }
+        }));
+        assert!(hls_supports_cabal_fmt(&schema));
+        assert!(!hls_supports_cabal_fmt(&serde_json::json!({})));
+
+        assert_eq!(
+            cabal_language_settings("/ext/cabal-fmt/cabal-fmt", false),
+            serde_json::json!({
+                "formatter": {
+                    "external": { "command": "/ext/cabal-fmt/cabal-fmt", "arguments": [] }
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_disabled_plugin() {
+        let settings = serde_json::json!({
+            "formattingProvider": "fourmolu",
+            "plugin": { "fourmolu": { "globalOn": false } }
+        });
+
+        assert!(plugin_disabled(&settings, "fourmolu"));
+        assert!(!plugin_disabled(&settings, "ormolu"));
+        assert_eq!(
+            external_command("fourmolu", "${INPUT}").as_deref(),
+            Some("fourmolu --stdin-input-file ${INPUT}")
+        );
+        assert_eq!(
+            external_command("stylish-haskell", "${INPUT}").as_deref(),
+            Some("stylish-haskell")
+        );
+        assert_eq!(external_command("none", "${INPUT}"), None);
+    }
+}
@@ -0,0 +1,136 @@
+use std::collections::BTreeSet;
+
+/// Returns the problems found when comparing `stack.yaml` with its
+/// `stack.yaml.lock`.
+pub fn stack_lock_drift(stack_yaml: &crate::stackage::StackYaml, lock: &str) -> Vec<String> {
+    let mut locked_snapshots = BTreeSet::new();
+    let mut locked_packages = BTreeSet::new();
+    let mut in_original = false;
+    for line in lock.lines() {
+        let trimmed = line.trim().trim_start_matches("- ");
+        if let Some(original) = trimmed.strip_prefix("original:") {
+            let original = original.trim();
+            if original.is_empty() {
+                in_original = true;
+            } else {
+                locked_snapshots.insert(original.to_string());
+            }
+            continue;
+        }
+        if in_original {
+            if let Some(package) = trimmed.strip_prefix("hackage:") {
+                let package = package.trim();
+                let package = package.split('@').next().unwrap_or(package);
+                locked_packages.insert(package.to_string());
+            }
+        }
+        in_original = false;
+    }
+
+    let mut problems = Vec::new();
+    if !locked_snapshots.contains(&stack_yaml.resolver) {
+        problems.push(format!(
+            "resolver `{}` is not the snapshot recorded in stack.yaml.lock",
+            stack_yaml.resolver
+        ));
+    }
+    let extra_deps: BTreeSet<String> = stack_yaml
+        .extra_deps
+        .iter()
+        .map(|(name, version)| format!("{name}-{version}"))
+        .collect();
+    for dep in extra_deps.difference(&locked_packages) {
+        problems.push(format!("extra dep `{dep}` is missing from stack.yaml.lock"));
+    }
+    for dep in locked_packages.difference(&extra_deps) {
+        problems.push(format!(
+            "stack.yaml.lock pins `{dep}`, which is no longer an extra dep"
+        ));
+    }
+    problems
+}
+
+/// Returns the problems found when comparing a package's dependencies
+/// with `cabal.project.freeze`.
+pub fn cabal_freeze_drift(dependencies: &[String], freeze: &str) -> Vec<String> {
+    let frozen: BTreeSet<&str> = freeze
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim().trim_start_matches("constraints:").trim();
+            let constraint = line.split_whitespace().next()?;
+            let name = constraint.strip_prefix("any.").unwrap_or(constraint);
+            line.contains("==").then_some(name)
+        })
+        .collect();
+
+    dependencies
+        .iter()
+        .filter(|dep| !frozen.contains(dep.as_str()))
+        .map(|dep| format!("dependency `{dep}` is not pinned in cabal.project.freeze"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stack_lock_drift() {
+        let stack_yaml = crate::stackage::parse_stack_yaml(
+            "resolver: lts-22.44\nextra-deps:\n- text-2.1\n- brick-2.3\n",
+        )
+        .unwrap();
+        let lock = "\
+packages:
+- completed:
+    hackage: text-2.1@sha256:abc,123
+    pantry-tree:
+      sha256: def
+      size: 456
+  original:
+    hackage: text-2.1
+- completed:
+    hackage: vty-5.38@sha256:abc,123
+  original:
+    hackage: vty-5.38
+snapshots:
+- completed:
+    sha256: 789
+    size: 718
+    url: https://raw.githubusercontent.com/commercialhaskell/stackage-snapshots/master/lts/21/25.yaml
+  original: lts-21.25
+";
+
+        assert_eq!(
+            stack_lock_drift(&stack_yaml, lock),
+            vec![
+                "resolver `lts-22.44` is not the snapshot recorded in stack.yaml.lock",
+                "extra dep `brick-2.3` is missing from stack.yaml.lock",
+                "stack.yaml.lock pins `vty-5.38`, which is no longer an extra dep",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cabal_freeze_drift() {
+        let dependencies = vec![
+            "base".to_string(),
+            "containers".to_string(),
+            "optparse-applicative".to_string(),
+            "text".to_string(),
+        ];
+
+        let freeze = "\
+active-repositories: hackage.haskell.org:merge
+constraints: any.base ==4.18.2.1,
+             any.text ==2.0.2,
+             any.text -simdutf,
+             any.containers ==0.6.7
+index-state: hackage.haskell.org 2024-06-01T00:00:00Z
+";
+        assert_eq!(
+            cabal_freeze_drift(&dependencies, freeze),
+            vec!["dependency `optparse-applicative` is not pinned in cabal.project.freeze"]
+        );
+    }
+}
@@ -1,14 +1,55 @@
-use zed::lsp::{Symbol, SymbolKind};
-use zed::{CodeLabel, CodeLabelSpan};
+mod cabal_file;
+mod cradle;
+mod dap;
+mod default_config;
+mod extension_log;
+mod extension_settings;
+mod fallback_diagnostics;
+mod formatter;
+mod freeze;
+mod hie_yaml;
+mod hoogle;
+mod labels;
+mod property_stub;
+mod schema_cache;
+mod stackage;
+mod stan;
+mod validation;
+mod vscode_settings;
+mod weeder;
+mod yaml_schemas;
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::Mutex;
+
+use extension_settings::{ExtensionSettings, Feature};
+use zed::lsp::{Completion, Symbol};
+use zed::{
+    CodeLabel, DebugAdapterBinary, DebugConfig, DebugRequest, DebugScenario, DebugTaskDefinition,
+    SlashCommand, SlashCommandArgumentCompletion, SlashCommandOutput, SlashCommandOutputSection,
+    StartDebuggingRequestArguments, StartDebuggingRequestArgumentsRequest, TaskTemplate,
+};
 use zed_extension_api::process::Command;
 use zed_extension_api::settings::LspSettings;
 use zed_extension_api::{self as zed, Result};
 
-struct HaskellExtension;
+#[derive(Default)]
+struct HaskellExtension {
+    /// Raw settings schemas by HLS binary path, see `hls_schema`.
+    schemas: Mutex<HashMap<String, Option<serde_json::Value>>>,
+    /// Hashes of the settings `check_settings` last accepted, by source and
+    /// HLS binary path.
+    checked_settings: Mutex<HashMap<(String, String), u64>>,
+    /// The environment each HLS binary was started with, for running it
+    /// where there is no worktree.
+    hls_envs: Mutex<HashMap<String, Vec<(String, String)>>>,
+}
 
 impl zed::Extension for HaskellExtension {
     fn new() -> Self {
-        Self
+        Self::default()
     }
 
     fn language_server_command(
@@ -17,83 +58,1149 @@ impl zed::Extension for HaskellExtension {
         worktree: &zed::Worktree,
     ) -> Result<zed::Command> {
         let lsp_settings = LspSettings::for_worktree(language_server_id.as_ref(), worktree)?;
+        let fallback_diagnostics = uses_fallback_diagnostics(&lsp_settings, worktree);
+        let binary_settings = lsp_settings.binary;
+
+        // Variables from `binary.env` only apply to HLS, not to tasks.
+        let env_overrides = binary_settings
+            .as_ref()
+            .and_then(|binary_settings| binary_settings.env.clone())
+            .unwrap_or_default();
+        if !env_overrides.is_empty() {
+            let mut names: Vec<&String> = env_overrides.keys().collect();
+            names.sort();
+            extension_log::log(format!(
+                "overriding {names:?} in the environment of HLS for {}",
+                worktree.root_path()
+            ));
+        }
+        let env = merge_env(worktree.shell_env(), env_overrides);
 
         // If the user has specified a binary in their LSP settings,
         // that takes precedence.
-        if let Some(binary_settings) = lsp_settings.binary {
+        if let Some(binary_settings) = binary_settings {
             if let Some(path) = binary_settings.path {
+                extension_log::log(format!(
+                    "using {path} from the LSP settings for {}",
+                    worktree.root_path()
+                ));
+                self.record_hls_env(&path, &env);
                 return Ok(zed::Command {
                     command: path,
                     args: binary_settings.arguments.unwrap_or_else(Vec::new),
-                    env: worktree.shell_env(),
+                    env,
                 });
             }
         }
 
+        if fallback_diagnostics {
+            let haskell_settings = haskell_settings(
+                lsp_settings.initialization_options.as_ref(),
+                lsp_settings.settings.as_ref(),
+            );
+            let provider = haskell_settings
+                .iter()
+                .find_map(formatter::configured_provider);
+            return fallback_diagnostics::hlint_command(worktree, env, provider);
+        }
+
         // Otherwise, default to hls installed via ghcup.
-        let path = worktree
-            .which("haskell-language-server-wrapper")
-            .ok_or_else(|| "hls must be installed via ghcup".to_string())?;
+        let Some(path) = worktree.which("haskell-language-server-wrapper") else {
+            return Err("hls must be installed via ghcup".to_string());
+        };
+        extension_log::log(format!(
+            "using {path} from PATH for {}",
+            worktree.root_path()
+        ));
+        self.record_hls_env(&path, &env);
 
         Ok(zed::Command {
             command: path,
             args: vec!["lsp".to_string()],
-            env: worktree.shell_env(),
+            env,
         })
     }
 
+    fn label_for_completion(
+        &self,
+        _language_server_id: &zed::LanguageServerId,
+        completion: Completion,
+    ) -> Option<CodeLabel> {
+        labels::completion_label(
+            &completion.label,
+            completion.kind?,
+            completion.detail.as_deref(),
+            completion
+                .label_details
+                .as_ref()
+                .and_then(|details| details.description.as_deref()),
+        )
+        .map(Into::into)
+    }
+
     fn label_for_symbol(
         &self,
         _language_server_id: &zed::LanguageServerId,
         symbol: Symbol,
     ) -> Option<CodeLabel> {
-        let name = &symbol.name;
-
-        let (code, display_range, filter_range) = match symbol.kind {
-            SymbolKind::Struct => {
-                let data_decl = "data ";
-                let code = format!("{data_decl}{name} = A");
-                let display_range = 0..data_decl.len() + name.len();
-                let filter_range = data_decl.len()..display_range.end;
-                (code, display_range, filter_range)
-            }
-            SymbolKind::Constructor => {
-                let data_decl = "data A = ";
-                let code = format!("{data_decl}{name}");
-                let display_range = data_decl.len()..data_decl.len() + name.len();
-                let filter_range = 0..name.len();
-                (code, display_range, filter_range)
-            }
-            SymbolKind::Variable => {
-                let code = format!("{name} :: T");
-                let display_range = 0..name.len();
-                let filter_range = 0..name.len();
-                (code, display_range, filter_range)
-            }
-            _ => return None,
+        labels::symbol_label(&symbol.name, symbol.kind).map(Into::into)
+    }
+
+    fn language_server_initialization_options(
+        &mut self,
+        language_server_id: &zed::LanguageServerId,
+        worktree: &zed::Worktree,
+    ) -> Result<Option<serde_json::Value>> {
+        let lsp_settings = LspSettings::for_worktree(language_server_id.as_ref(), worktree)?;
+        if uses_fallback_diagnostics(&lsp_settings, worktree) {
+            return Ok(None);
+        }
+        let extension_settings =
+            ExtensionSettings::from_lsp_settings(lsp_settings.settings.as_ref());
+        let mut options = default_initialization_options();
+        if let Some(mut user_options) = lsp_settings.initialization_options {
+            if let Some(haskell_options) = user_options.get_mut("haskell") {
+                vscode_settings::expand_dotted_keys(haskell_options);
+            }
+            self.check_settings(
+                "initialization_options",
+                &user_options,
+                worktree,
+                &extension_settings,
+            )?;
+            merge_json(&mut options, user_options);
+        }
+        Ok(Some(options))
+    }
+
+    fn language_server_workspace_configuration(
+        &mut self,
+        language_server_id: &zed::LanguageServerId,
+        worktree: &zed::Worktree,
+    ) -> Result<Option<serde_json::Value>> {
+        let lsp_settings = LspSettings::for_worktree(language_server_id.as_ref(), worktree)?;
+        if uses_fallback_diagnostics(&lsp_settings, worktree) {
+            return Ok(None);
+        }
+        let extension_settings =
+            ExtensionSettings::from_lsp_settings(lsp_settings.settings.as_ref());
+        let mut settings = lsp_settings.settings;
+        if let Some(settings) = &mut settings {
+            extension_settings::strip(settings);
+            if let Some(haskell_settings) = settings.get_mut("haskell") {
+                vscode_settings::expand_dotted_keys(haskell_settings);
+            }
+            self.check_settings("settings", settings, worktree, &extension_settings)?;
+        }
+        Ok(settings)
+    }
+
+    fn language_server_initialization_options_schema(&self, binary_path: String) -> Option<String> {
+        let env = self
+            .hls_envs
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&binary_path)
+            .cloned()
+            .unwrap_or_default();
+        let (raw_schema, description) = match self.hls_schema(&binary_path, env) {
+            Some(value) => (value, None),
+            None => {
+                extension_log::log(format!(
+                    "{binary_path} didn't provide a settings schema, using the bundled one"
+                ));
+                let description = format!(
+                    "`{binary_path}` didn't provide a settings schema, so this is a bundled \
+                     copy that may be out of date for your HLS version."
+                );
+                (bundled_schema(), Some(description))
+            }
+        };
+        let mut haskell_schema = convert_to_zed_schema(&raw_schema);
+        add_dotted_properties(&mut haskell_schema, &raw_schema);
+        if let Some(description) = description {
+            haskell_schema["markdownDescription"] = description.into();
+        }
+        // HLS reads its configuration from the `haskell` key.
+        let schema = serde_json::json!({
+            "type": "object",
+            "properties": {
+                "haskell": haskell_schema
+            }
+        });
+        Some(schema.to_string())
+    }
+
+    fn get_dap_binary(
+        &mut self,
+        adapter_name: String,
+        config: DebugTaskDefinition,
+        user_provided_debug_adapter_path: Option<String>,
+        worktree: &zed::Worktree,
+    ) -> Result<DebugAdapterBinary> {
+        if adapter_name != dap::ADAPTER_NAME {
+            return Err(format!("unknown debug adapter {adapter_name}"));
+        }
+        let lsp_settings = LspSettings::for_worktree("hls", worktree)?;
+        let extension_settings =
+            ExtensionSettings::from_lsp_settings(lsp_settings.settings.as_ref());
+        if !extension_settings.enabled(Feature::Dap) {
+            return Err(format!(
+                "debugging Haskell is experimental; set \"features\": {{ \"{}\": true }} in lsp.hls.settings.{} to enable it",
+                Feature::Dap.name(),
+                extension_settings::KEY
+            ));
+        }
+
+        let binaries = match user_provided_debug_adapter_path {
+            Some(adapter) => dap::Binaries {
+                adapter,
+                ghci_dap: "ghci-dap".to_string(),
+            },
+            None => dap::find_or_install(worktree)?,
         };
+        let command = binaries.adapter;
+        extension_log::log(format!(
+            "using {command} to debug {} in {}",
+            config.label,
+            worktree.root_path()
+        ));
+
+        let user_config: serde_json::Value =
+            serde_json::from_str(&config.config).map_err(|e| e.to_string())?;
+        let stack_project = worktree.read_text_file("stack.yaml").is_ok();
+        let configuration = dap::with_defaults(
+            user_config,
+            &worktree.root_path(),
+            stack_project,
+            &binaries.ghci_dap,
+        );
+        let request = self.dap_request_kind(adapter_name, configuration.clone())?;
 
-        Some(CodeLabel {
-            spans: vec![CodeLabelSpan::code_range(display_range)],
-            filter_range: filter_range.into(),
-            code,
+        Ok(DebugAdapterBinary {
+            command: Some(command),
+            arguments: Vec::new(),
+            envs: worktree.shell_env(),
+            cwd: Some(worktree.root_path()),
+            connection: None,
+            request_args: StartDebuggingRequestArguments {
+                configuration: configuration.to_string(),
+                request,
+            },
         })
     }
 
-    fn language_server_initialization_options_schema(&self, binary_path: String) -> Option<String> {
-        // This is more difficult to do asynchronously...
-        let output = Command::new(binary_path)
-            .arg("vscode-extension-schema")
-            .output()
-            .ok()?;
-        if output.status != Some(0) {
+    fn dap_request_kind(
+        &mut self,
+        _adapter_name: String,
+        config: serde_json::Value,
+    ) -> Result<StartDebuggingRequestArgumentsRequest> {
+        match config.get("request").and_then(|request| request.as_str()) {
+            Some("launch") | None => Ok(StartDebuggingRequestArgumentsRequest::Launch),
+            Some("attach") => Err("haskell-debug-adapter can't attach to running programs".into()),
+            Some(request) => Err(format!("unknown debug request {request:?}")),
+        }
+    }
+
+    fn dap_config_to_scenario(&mut self, config: DebugConfig) -> Result<DebugScenario> {
+        let DebugRequest::Launch(launch) = config.request else {
+            return Err("haskell-debug-adapter can't attach to running programs".into());
+        };
+        let mut debug_config = dap::launch_config(
+            &launch.program,
+            &launch.args,
+            config.stop_on_entry.unwrap_or(false),
+        );
+        if let Some(cwd) = launch.cwd {
+            debug_config["workspace"] = cwd.into();
+        }
+        if !launch.envs.is_empty() {
+            debug_config["ghciEnv"] = launch
+                .envs
+                .into_iter()
+                .map(|(key, value)| (key, value.into()))
+                .collect::<serde_json::Map<_, _>>()
+                .into();
+        }
+        Ok(DebugScenario {
+            label: config.label,
+            adapter: config.adapter,
+            build: None,
+            config: debug_config.to_string(),
+            tcp_connection: None,
+        })
+    }
+
+    fn dap_locator_create_scenario(
+        &mut self,
+        locator_name: String,
+        build_task: TaskTemplate,
+        resolved_label: String,
+        debug_adapter_name: String,
+    ) -> Option<DebugScenario> {
+        if locator_name != dap::LOCATOR_NAME || debug_adapter_name != dap::ADAPTER_NAME {
             return None;
         }
-        let data = String::from_utf8_lossy(&output.stdout);
-        // The schema emitted is not the one used by Zed.
-        let value: serde_json::Value = serde_json::from_str(&data).ok()?;
-        Some(convert_to_zed_schema(&value).to_string())
+        let config = dap::locate(&build_task.command, &build_task.args, &build_task.env)?;
+        // ghci-dap interprets the program, so there is nothing to build
+        // first and no second phase for `run_dap_locator`.
+        Some(DebugScenario {
+            label: resolved_label,
+            adapter: debug_adapter_name,
+            build: None,
+            config: config.to_string(),
+            tcp_connection: None,
+        })
+    }
+
+    fn complete_slash_command_argument(
+        &self,
+        command: SlashCommand,
+        _args: Vec<String>,
+    ) -> Result<Vec<SlashCommandArgumentCompletion>> {
+        match command.name.as_str() {
+            "hls-toggle-plugin" => {
+                let schema = convert_to_zed_schema(&bundled_schema());
+                let plugins = schema["properties"]["plugin"]["properties"]
+                    .as_object()
+                    .map(|plugins| plugins.keys().cloned().collect::<Vec<_>>())
+                    .unwrap_or_default();
+                Ok(plugins
+                    .into_iter()
+                    .map(|plugin| SlashCommandArgumentCompletion {
+                        label: plugin.clone(),
+                        new_text: plugin,
+                        run_command: true,
+                    })
+                    .collect())
+            }
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    fn run_slash_command(
+        &self,
+        command: SlashCommand,
+        args: Vec<String>,
+        worktree: Option<&zed::Worktree>,
+    ) -> Result<SlashCommandOutput> {
+        match command.name.as_str() {
+            "haskell-extension-log" => {
+                let entries = extension_log::entries();
+                let mut text = if entries.is_empty() {
+                    "The extension hasn't logged anything yet.".to_string()
+                } else {
+                    entries.join("\n")
+                };
+                if let Some(worktree) = worktree {
+                    let lsp_settings = LspSettings::for_worktree("hls", worktree)?;
+                    let extension_settings =
+                        ExtensionSettings::from_lsp_settings(lsp_settings.settings.as_ref());
+                    let features: Vec<&str> = Feature::ALL
+                        .into_iter()
+                        .filter(|feature| extension_settings.enabled(*feature))
+                        .map(Feature::name)
+                        .collect();
+                    let features = if features.is_empty() {
+                        "none".to_string()
+                    } else {
+                        features.join(", ")
+                    };
+                    text = format!("Enabled features: {features}\n\n{text}");
+                }
+                Ok(SlashCommandOutput {
+                    sections: vec![SlashCommandOutputSection {
+                        range: (0..text.len()).into(),
+                        label: "Haskell extension log".to_string(),
+                    }],
+                    text,
+                })
+            }
+            "hls-default-settings" => {
+                let worktree = worktree.ok_or("no worktree")?;
+                let binary_path = hls_binary_path(worktree)?;
+                let value = self
+                    .hls_schema(&binary_path, worktree.shell_env())
+                    .ok_or_else(|| format!("{binary_path} did not provide a settings schema"))?;
+                let defaults = default_settings(&convert_to_zed_schema(&value));
+                settings_snippet("HLS default settings", defaults)
+            }
+            "hls-toggle-plugin" => {
+                let worktree = worktree.ok_or("no worktree")?;
+                let plugin = args.first().ok_or("expected a plugin name")?;
+                let lsp_settings = LspSettings::for_worktree("hls", worktree)?;
+                let schema = convert_to_zed_schema(&bundled_schema());
+                let enabled = [lsp_settings.initialization_options, lsp_settings.settings]
+                    .iter()
+                    .flatten()
+                    .find_map(|settings| {
+                        settings["haskell"]["plugin"][plugin]["globalOn"].as_bool()
+                    })
+                    .or_else(|| {
+                        schema["properties"]["plugin"]["properties"][plugin]["properties"]
+                            ["globalOn"]["default"]
+                            .as_bool()
+                    })
+                    .unwrap_or(true);
+                let settings = serde_json::json!({
+                    "plugin": {
+                        plugin: {
+                            "globalOn": !enabled
+                        }
+                    }
+                });
+                let action = if enabled { "Disable" } else { "Enable" };
+                settings_snippet(&format!("{action} the {plugin} plugin"), settings)
+            }
+            "hls-import-vscode-settings" => {
+                let worktree = worktree.ok_or("no worktree")?;
+                let path = args
+                    .first()
+                    .map(String::as_str)
+                    .unwrap_or(".vscode/settings.json");
+                let contents = worktree.read_text_file(path)?;
+                let imported = vscode_settings::convert(&contents)?;
+                let mut hls = serde_json::json!({
+                    "initialization_options": { "haskell": imported.haskell }
+                });
+                if !imported.binary.is_empty() {
+                    hls["binary"] = serde_json::Value::Object(imported.binary);
+                }
+                let snippet = serde_json::json!({ "lsp": { "hls": hls } });
+                let json = serde_json::to_string_pretty(&snippet).map_err(|e| e.to_string())?;
+                // Extensions can't write to the worktree.
+                let mut text =
+                    format!("Merge this into .zed/settings.json:\n\n```json\n{json}\n```\n");
+                if !imported.dropped.is_empty() {
+                    text.push_str(&format!(
+                        "\nThese settings of the VSCode extension have no equivalent in Zed: {}\n",
+                        imported.dropped.join(", ")
+                    ));
+                }
+                Ok(SlashCommandOutput {
+                    sections: vec![SlashCommandOutputSection {
+                        range: (0..text.len()).into(),
+                        label: "HLS settings imported from VSCode".to_string(),
+                    }],
+                    text,
+                })
+            }
+            "haskell-property-stub" => {
+                let (framework, signature) = match args.split_first() {
+                    Some((first, rest)) => match property_stub::Framework::from_name(first) {
+                        Some(framework) => (framework, rest),
+                        None => (property_stub::Framework::QuickCheck, args.as_slice()),
+                    },
+                    None => return Err("expected a type signature".to_string()),
+                };
+                let stub = property_stub::generate(&signature.join(" "), framework)?;
+                let text = format!("```haskell\n{stub}```");
+                Ok(SlashCommandOutput {
+                    sections: vec![SlashCommandOutputSection {
+                        range: (0..text.len()).into(),
+                        label: "Property stub".to_string(),
+                    }],
+                    text,
+                })
+            }
+            "haskell-formatter-settings" => {
+                let worktree = worktree.ok_or("no worktree")?;
+                let binary_path = hls_binary_path(worktree)?;
+                let schema = self
+                    .hls_schema(&binary_path, worktree.shell_env())
+                    .map(|value| convert_to_zed_schema(&value))
+                    .unwrap_or_default();
+                let lsp_settings = LspSettings::for_worktree("hls", worktree)?;
+                let haskell_settings = haskell_settings(
+                    lsp_settings.initialization_options.as_ref(),
+                    lsp_settings.settings.as_ref(),
+                );
+                let provider = match args.first() {
+                    Some(provider) => provider.clone(),
+                    None => haskell_settings
+                        .iter()
+                        .find_map(formatter::configured_provider)
+                        .unwrap_or("ormolu")
+                        .to_string(),
+                };
+                // A disabled plugin is as good as a missing one.
+                let hls_formats = formatter::hls_supports(&schema, &provider)
+                    && !haskell_settings
+                        .iter()
+                        .any(|settings| formatter::plugin_disabled(settings, &provider));
+                let extension_settings =
+                    ExtensionSettings::from_lsp_settings(lsp_settings.settings.as_ref());
+                let language_settings = formatter::zed_language_settings(
+                    &provider,
+                    hls_formats,
+                    extension_settings.stylish_haskell,
+                );
+
+                let mut text = String::new();
+                let mut sections = Vec::new();
+                let haskell_snippet = serde_json::json!({
+                    "languages": { "Haskell": language_settings }
+                });
+                let json =
+                    serde_json::to_string_pretty(&haskell_snippet).map_err(|e| e.to_string())?;
+                text.push_str(&format!("```json\n{json}\n```\n"));
+                sections.push(SlashCommandOutputSection {
+                    range: (0..text.len()).into(),
+                    label: format!("Format Haskell with {provider}"),
+                });
+
+                // HLS can only run a cabal-fmt it finds on the PATH.
+                let cabal_fmt = match worktree.which("cabal-fmt") {
+                    Some(cabal_fmt) => {
+                        Some((cabal_fmt, formatter::hls_supports_cabal_fmt(&schema)))
+                    }
+                    None => formatter::installed_cabal_fmt().map(|cabal_fmt| (cabal_fmt, false)),
+                };
+                let start = text.len();
+                match cabal_fmt {
+                    Some((cabal_fmt, through_hls)) => {
+                        let json = serde_json::to_string_pretty(&cabal_fmt_snippet(
+                            &cabal_fmt,
+                            through_hls,
+                        ))
+                        .map_err(|e| e.to_string())?;
+                        text.push_str(&format!("```json\n{json}\n```\n"));
+                    }
+                    None => text.push_str(
+                        "cabal-fmt is not installed. Put it on your PATH, or run \
+                         /haskell-install-cabal-fmt to install it with cabal.\n",
+                    ),
+                }
+                sections.push(SlashCommandOutputSection {
+                    range: (start..text.len()).into(),
+                    label: "Format .cabal files with cabal-fmt".to_string(),
+                });
+                Ok(SlashCommandOutput { sections, text })
+            }
+            "haskell-install-cabal-fmt" => {
+                let worktree = worktree.ok_or("no worktree")?;
+                let cabal_fmt = formatter::install_cabal_fmt(worktree)?;
+                let json = serde_json::to_string_pretty(&cabal_fmt_snippet(&cabal_fmt, false))
+                    .map_err(|e| e.to_string())?;
+                let text = format!("Installed {cabal_fmt}.\n\n```json\n{json}\n```\n");
+                Ok(SlashCommandOutput {
+                    sections: vec![SlashCommandOutputSection {
+                        range: (0..text.len()).into(),
+                        label: "Format .cabal files with cabal-fmt".to_string(),
+                    }],
+                    text,
+                })
+            }
+            "generate-hie-yaml" => {
+                let worktree = worktree.ok_or("no worktree")?;
+                let cradle = hie_yaml::generate(worktree, args.first().map(String::as_str))?;
+                let text =
+                    format!("Save this as hie.yaml in the project root:\n\n```yaml\n{cradle}```");
+                Ok(SlashCommandOutput {
+                    sections: vec![SlashCommandOutputSection {
+                        range: (0..text.len()).into(),
+                        label: "hie.yaml".to_string(),
+                    }],
+                    text,
+                })
+            }
+            "haskell-check-freeze" => {
+                let worktree = worktree.ok_or("no worktree")?;
+                let mut text = String::new();
+
+                if let Ok(lock) = worktree.read_text_file("stack.yaml.lock") {
+                    let stack_yaml =
+                        stackage::parse_stack_yaml(&worktree.read_text_file("stack.yaml")?)?;
+                    let problems = freeze::stack_lock_drift(&stack_yaml, &lock);
+                    text.push_str(&drift_report(
+                        "stack.yaml.lock",
+                        &problems,
+                        "stack build --dry-run",
+                    ));
+                }
+                if let Ok(freeze_file) = worktree.read_text_file("cabal.project.freeze") {
+                    let dependencies = match worktree.read_text_file("package.yaml") {
+                        Ok(package_yaml) => stackage::package_yaml_dependencies(&package_yaml),
+                        Err(_) => cabal_file::build_depends(
+                            &cabal_file::read(worktree, args.first().map(String::as_str))?.1,
+                            cabal_file::ghc_version(worktree).as_deref(),
+                        ),
+                    };
+                    let problems = freeze::cabal_freeze_drift(&dependencies, &freeze_file);
+                    text.push_str(&drift_report(
+                        "cabal.project.freeze",
+                        &problems,
+                        "cabal freeze",
+                    ));
+                }
+                if text.is_empty() {
+                    return Err(
+                        "found neither stack.yaml.lock nor cabal.project.freeze".to_string()
+                    );
+                }
+
+                Ok(SlashCommandOutput {
+                    sections: vec![SlashCommandOutputSection {
+                        range: (0..text.len()).into(),
+                        label: "Freeze file drift".to_string(),
+                    }],
+                    text,
+                })
+            }
+            "stack-upgrade-resolver" => {
+                let worktree = worktree.ok_or("no worktree")?;
+                let contents = worktree.read_text_file("stack.yaml")?;
+                let stack_yaml = stackage::parse_stack_yaml(&contents)?;
+                let new_resolver = match args.first() {
+                    Some(resolver) => resolver.clone(),
+                    None => stackage::latest_lts()?,
+                };
+                if new_resolver == stack_yaml.resolver {
+                    return Err(format!("stack.yaml is already on {new_resolver}"));
+                }
+                let dependencies = worktree
+                    .read_text_file("package.yaml")
+                    .map(|contents| stackage::package_yaml_dependencies(&contents))
+                    .unwrap_or_default();
+                let plan = stackage::plan_upgrade(
+                    &stack_yaml.extra_deps,
+                    &dependencies,
+                    &stackage::snapshot_packages(&stack_yaml.resolver)?,
+                    &stackage::snapshot_packages(&new_resolver)?,
+                );
+
+                let mut text = format!(
+                    "Upgrading `{}` to `{new_resolver}`.\n\n",
+                    stack_yaml.resolver
+                );
+                if !plan.unnecessary.is_empty() {
+                    text.push_str("Extra deps now provided by the snapshot:\n");
+                    for dep in &plan.unnecessary {
+                        text.push_str(&format!("- {dep}\n"));
+                    }
+                    text.push('\n');
+                }
+                if !plan.newly_required.is_empty() {
+                    text.push_str(
+                        "Dependencies dropped from the snapshot, add them to `extra-deps`:\n",
+                    );
+                    for dep in &plan.newly_required {
+                        text.push_str(&format!("- {dep}\n"));
+                    }
+                    text.push('\n');
+                }
+                let section_start = text.len();
+                text.push_str(&format!(
+                    "```yaml\n{}```",
+                    stackage::apply_upgrade(&contents, &new_resolver, &plan.unnecessary)
+                ));
+                Ok(SlashCommandOutput {
+                    sections: vec![SlashCommandOutputSection {
+                        range: (section_start..text.len()).into(),
+                        label: "stack.yaml".to_string(),
+                    }],
+                    text,
+                })
+            }
+            "haskell-yaml-schemas" => {
+                let snippet = serde_json::json!({
+                    "lsp": {
+                        "yaml-language-server": {
+                            "settings": {
+                                "yaml": {
+                                    "schemas": yaml_schemas::install()?
+                                }
+                            }
+                        }
+                    }
+                });
+                let json = serde_json::to_string_pretty(&snippet).map_err(|e| e.to_string())?;
+                let text = format!("```json\n{json}\n```");
+                Ok(SlashCommandOutput {
+                    sections: vec![SlashCommandOutputSection {
+                        range: (0..text.len()).into(),
+                        label: "YAML schemas for Haskell projects".to_string(),
+                    }],
+                    text,
+                })
+            }
+            "haskell-debug-scenarios" => {
+                let worktree = worktree.ok_or("no worktree")?;
+                let (file_name, cabal_file) =
+                    cabal_file::read(worktree, args.first().map(String::as_str))?;
+                let ghc = cabal_file::ghc_version(worktree);
+                let components = cabal_file::components(&cabal_file, ghc.as_deref());
+                let scenarios =
+                    dap::scenarios(&components, |path| worktree.read_text_file(path).is_ok());
+                if scenarios.as_array().is_some_and(Vec::is_empty) {
+                    return Err(format!(
+                        "{file_name} has no executable or test suite with a main-is"
+                    ));
+                }
+                let json = serde_json::to_string_pretty(&scenarios).map_err(|e| e.to_string())?;
+                let text = format!("```json\n{json}\n```");
+                Ok(SlashCommandOutput {
+                    sections: vec![SlashCommandOutputSection {
+                        range: (0..text.len()).into(),
+                        label: "Debug scenarios for .zed/debug.json".to_string(),
+                    }],
+                    text,
+                })
+            }
+            "haskell-weeder" => {
+                let worktree = worktree.ok_or("no worktree")?;
+                let text = weeder::report(&weeder::run(worktree)?);
+                Ok(SlashCommandOutput {
+                    sections: vec![SlashCommandOutputSection {
+                        range: (0..text.len()).into(),
+                        label: "Unused declarations".to_string(),
+                    }],
+                    text,
+                })
+            }
+            "haskell-stan" => {
+                let worktree = worktree.ok_or("no worktree")?;
+                let text = stan::report(&stan::run(worktree, args.first().map(String::as_str))?);
+                Ok(SlashCommandOutput {
+                    sections: vec![SlashCommandOutputSection {
+                        range: (0..text.len()).into(),
+                        label: "stan observations".to_string(),
+                    }],
+                    text,
+                })
+            }
+            "haskell-hoogle" => {
+                let query = args.join(" ");
+                if query.is_empty() {
+                    return Err("usage: /haskell-hoogle <name or type>".to_string());
+                }
+                let hits = hoogle::search(&query, worktree)?;
+                let (text, sections) = hoogle::render(&query, &hits);
+                Ok(SlashCommandOutput {
+                    sections: sections
+                        .into_iter()
+                        .map(|(range, label)| SlashCommandOutputSection {
+                            range: range.into(),
+                            label,
+                        })
+                        .collect(),
+                    text,
+                })
+            }
+            "haskell-diagnose-cradle" => {
+                let worktree = worktree.ok_or("no worktree")?;
+                let file = args
+                    .first()
+                    .ok_or("usage: /haskell-diagnose-cradle <file relative to the worktree>")?;
+                let diagnosis = cradle::diagnose(worktree, &hls_binary_path(worktree)?, file)?;
+                let (text, sections) = cradle::render(file, &diagnosis);
+                Ok(SlashCommandOutput {
+                    sections: sections
+                        .into_iter()
+                        .map(|(range, label)| SlashCommandOutputSection {
+                            range: range.into(),
+                            label,
+                        })
+                        .collect(),
+                    text,
+                })
+            }
+            command => Err(format!("unknown slash command: \"{command}\"")),
+        }
+    }
+}
+
+impl HaskellExtension {
+    /// Returns the raw `vscode-extension-schema` output of the given HLS
+    /// binary, or one synthesized from its default config for older HLS
+    /// versions.
+    ///
+    /// Zed asks for the workspace configuration often, so the schema is
+    /// only loaded once per binary while the extension runs.
+    fn hls_schema(
+        &self,
+        binary_path: &str,
+        env: Vec<(String, String)>,
+    ) -> Option<serde_json::Value> {
+        let mut schemas = self.schemas.lock().unwrap_or_else(|e| e.into_inner());
+        schemas
+            .entry(binary_path.to_string())
+            .or_insert_with(|| load_hls_schema(binary_path, env))
+            .clone()
+    }
+
+    fn record_hls_env(&self, binary_path: &str, env: &[(String, String)]) {
+        self.hls_envs
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(binary_path.to_string(), env.to_vec());
+    }
+
+    /// Logs a warning for every HLS setting that doesn't match the schema of
+    /// the installed HLS, since HLS silently ignores them. Extensions can't
+    /// show notifications, so the warnings are only visible in Zed's log and
+    /// through `/haskell-extension-log`. In strict mode, settings HLS doesn't
+    /// know about are an error instead.
+    ///
+    /// Zed asks for the workspace configuration often, so settings are only
+    /// checked again once they change.
+    fn check_settings(
+        &self,
+        source: &str,
+        settings: &serde_json::Value,
+        worktree: &zed::Worktree,
+        extension_settings: &ExtensionSettings,
+    ) -> Result<()> {
+        let Some(haskell_settings) = settings.get("haskell") else {
+            return Ok(());
+        };
+        let binary_path = hls_binary_path(worktree).ok();
+        let key = (source.to_string(), binary_path.clone().unwrap_or_default());
+        let mut hasher = DefaultHasher::new();
+        haskell_settings.to_string().hash(&mut hasher);
+        extension_settings.strict.hash(&mut hasher);
+        let hash = hasher.finish();
+        let mut checked_settings = self
+            .checked_settings
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if checked_settings.get(&key) == Some(&hash) {
+            return Ok(());
+        }
+        let schema =
+            binary_path.and_then(|binary_path| self.hls_schema(&binary_path, worktree.shell_env()));
+        validate_settings(source, haskell_settings, schema, extension_settings)?;
+        checked_settings.insert(key, hash);
+        Ok(())
+    }
+}
+
+/// Does the work of `HaskellExtension::check_settings` against `schema`,
+/// the raw schema of the installed HLS.
+fn validate_settings(
+    source: &str,
+    haskell_settings: &serde_json::Value,
+    schema: Option<serde_json::Value>,
+    extension_settings: &ExtensionSettings,
+) -> Result<()> {
+    let Some(schema) = schema else {
+        if extension_settings.strict {
+            extension_log::log(format!(
+                "hls {source}: strict mode is on, but the settings schema is unavailable, \
+                 so unknown settings can't be detected"
+            ));
+        }
+        return Ok(());
+    };
+    let schema = convert_to_zed_schema(&schema);
+    if extension_settings.strict {
+        let unknown = validation::unknown_settings(&schema, haskell_settings);
+        if !unknown.is_empty() {
+            let unknown: Vec<String> = unknown.iter().map(|path| format!("`{path}`")).collect();
+            return Err(format!(
+                "strict mode: refusing to pass hls {source} with settings HLS doesn't know about: {}",
+                unknown.join(", ")
+            ));
+        }
+    }
+    for warning in validation::validate(&schema, haskell_settings) {
+        extension_log::log(format!("hls {source}: {warning}"));
+    }
+    if let Some(provider) = formatter::configured_provider(haskell_settings) {
+        if formatter::plugin_disabled(haskell_settings, provider) {
+            extension_log::log(format!(
+                "hls {source}: the {provider} plugin is disabled, so HLS won't format, \
+                 run /haskell-formatter-settings to format with {provider} directly"
+            ));
+        } else if !formatter::hls_supports(&schema, provider) {
+            extension_log::log(format!(
+                "hls {source}: HLS was built without the {provider} plugin, \
+                 run /haskell-formatter-settings to format with {provider} directly"
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Returns the Zed settings formatting `.cabal` files with `cabal_fmt`,
+/// through HLS or directly.
+fn cabal_fmt_snippet(cabal_fmt: &str, through_hls: bool) -> serde_json::Value {
+    let mut snippet = serde_json::json!({
+        "languages": {
+            "Cabal": formatter::cabal_language_settings(cabal_fmt, through_hls)
+        }
+    });
+    if through_hls {
+        snippet["lsp"] = serde_json::json!({
+            "hls": {
+                "initialization_options": {
+                    "haskell": { "cabalFormattingProvider": "cabal-fmt" }
+                }
+            }
+        });
+    }
+    snippet
+}
+
+/// Renders `settings` as the `lsp.hls` entry of a Zed settings file.
+fn settings_snippet(label: &str, settings: serde_json::Value) -> Result<SlashCommandOutput> {
+    let snippet = serde_json::json!({
+        "lsp": {
+            "hls": {
+                "initialization_options": {
+                    "haskell": settings
+                }
+            }
+        }
+    });
+    let json = serde_json::to_string_pretty(&snippet).map_err(|e| e.to_string())?;
+    let text = format!("```json\n{json}\n```");
+    Ok(SlashCommandOutput {
+        sections: vec![SlashCommandOutputSection {
+            range: (0..text.len()).into(),
+            label: label.to_string(),
+        }],
+        text,
+    })
+}
+
+fn drift_report(file: &str, problems: &[String], regenerate: &str) -> String {
+    if problems.is_empty() {
+        return format!("{file} is up to date.\n\n");
+    }
+    let mut report = format!("{file} is out of date:\n");
+    for problem in problems {
+        report.push_str(&format!("- {problem}\n"));
+    }
+    report.push_str(&format!("\nRun `{regenerate}` to regenerate it.\n\n"));
+    report
+}
+
+/// Returns the `haskell` settings from the initialization options and the
+/// workspace settings, in that order, with dotted keys expanded.
+fn haskell_settings(
+    initialization_options: Option<&serde_json::Value>,
+    settings: Option<&serde_json::Value>,
+) -> Vec<serde_json::Value> {
+    [initialization_options, settings]
+        .into_iter()
+        .flatten()
+        .filter_map(|settings| settings.get("haskell").cloned())
+        .map(|mut haskell_settings| {
+            vscode_settings::expand_dotted_keys(&mut haskell_settings);
+            haskell_settings
+        })
+        .collect()
+}
+
+/// Options we pass to HLS unless the user overrides them.
+fn default_initialization_options() -> serde_json::Value {
+    serde_json::json!({
+        "haskell": {
+            "maxCompletions": 40,
+            "plugin": {
+                "cabal": {
+                    "globalOn": true
+                }
+            }
+        }
+    })
+}
+
+/// Recursively merges `overrides` into `base`. Objects are merged key by
+/// key, any other value replaces what was there.
+fn merge_json(base: &mut serde_json::Value, overrides: serde_json::Value) {
+    match (base, overrides) {
+        (serde_json::Value::Object(base), serde_json::Value::Object(overrides)) => {
+            for (key, value) in overrides {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_json(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overrides) => *base = overrides,
+    }
+}
+
+/// Overrides variables of the shell environment, appending new ones.
+fn merge_env(
+    mut env: Vec<(String, String)>,
+    overrides: HashMap<String, String>,
+) -> Vec<(String, String)> {
+    for (key, value) in overrides {
+        match env.iter_mut().find(|(existing, _)| *existing == key) {
+            Some((_, existing)) => *existing = value,
+            None => env.push((key, value)),
+        }
+    }
+    env
+}
+
+/// Returns whether `language_server_command` starts efm-langserver instead
+/// of HLS, which mustn't be sent HLS's settings.
+fn uses_fallback_diagnostics(lsp_settings: &LspSettings, worktree: &zed::Worktree) -> bool {
+    let configured_path = lsp_settings
+        .binary
+        .as_ref()
+        .is_some_and(|binary| binary.path.is_some());
+    !configured_path
+        && worktree.which("haskell-language-server-wrapper").is_none()
+        && ExtensionSettings::from_lsp_settings(lsp_settings.settings.as_ref())
+            .enabled(Feature::FallbackDiagnostics)
+}
+
+/// Resolves the HLS binary the same way `language_server_command` does.
+fn hls_binary_path(worktree: &zed::Worktree) -> Result<String> {
+    let lsp_settings = LspSettings::for_worktree("hls", worktree)?;
+    if let Some(path) = lsp_settings.binary.and_then(|binary| binary.path) {
+        return Ok(path);
     }
+    worktree
+        .which("haskell-language-server-wrapper")
+        .ok_or_else(|| "hls must be installed via ghcup".to_string())
+}
+
+/// Loads the raw settings schema of an HLS binary, from the cache in the
+/// extension's work directory if it is still valid.
+fn load_hls_schema(binary_path: &str, env: Vec<(String, String)>) -> Option<serde_json::Value> {
+    // Spawning HLS can take several seconds, so reuse its last output
+    // as long as the binary and the GHC it delegates to report the same
+    // versions. The conversion itself is cheap and always redone, so
+    // converter changes take effect.
+    if let Err(e) = hls_command(binary_path, env.clone()) {
+        extension_log::log(e);
+        return None;
+    }
+    let version = schema_version(binary_path, &env);
+    let cached = version
+        .as_deref()
+        .and_then(|version| schema_cache::load(binary_path, version));
+    let data = match cached {
+        Some(data) => {
+            extension_log::log(format!("using the cached settings schema of {binary_path}"));
+            data
+        }
+        None => {
+            // HLS versions predating `vscode-extension-schema` can still
+            // print their default config, which a schema is synthesized from.
+            let data = ["vscode-extension-schema", "generate-default-config"]
+                .into_iter()
+                .find_map(|subcommand| {
+                    extension_log::log(format!("running {binary_path} {subcommand}"));
+                    // This is more difficult to do asynchronously...
+                    let output = hls_command(binary_path, env.clone())
+                        .ok()?
+                        .arg(subcommand)
+                        .output()
+                        .ok()?;
+                    (output.status == Some(0))
+                        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+                })?;
+            if let Some(version) = &version {
+                schema_cache::store(binary_path, version, &data);
+            }
+            data
+        }
+    };
+    // The schema emitted is not the one used by Zed.
+    let output: serde_json::Value = serde_json::from_str(&data).ok()?;
+    if default_config::is_default_config(&output) {
+        return Some(with_top_level_settings(default_config::to_raw_schema(
+            &output,
+        )));
+    }
+    Some(with_top_level_settings(output))
+}
+
+/// Identifies the schema `binary_path` prints. Besides its own version,
+/// this is the version of the GHC on the `PATH`, since the wrapper delegates
+/// to the HLS built for that GHC.
+fn schema_version(binary_path: &str, env: &[(String, String)]) -> Option<String> {
+    let numeric_version = |command: Command| {
+        command
+            .arg("--numeric-version")
+            .output()
+            .ok()
+            .filter(|output| output.status == Some(0))
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    };
+    let version = numeric_version(hls_command(binary_path, env.to_vec()).ok()?)?;
+    Some(
+        match numeric_version(Command::new("ghc").envs(env.to_vec())) {
+            Some(ghc_version) => format!("{version} (GHC {ghc_version})"),
+            None => version,
+        },
+    )
+}
+
+/// Names of the HLS binaries the extension's `process:exec` capabilities let
+/// it run.
+const HLS_BINARIES: &[&str] = &["haskell-language-server-wrapper", "haskell-language-server"];
+
+/// Returns a command running the HLS at `binary_path` with `env`.
+///
+/// Capabilities match commands by name rather than by path, so HLS is run
+/// by its file name, with its directory put first on the `PATH`.
+fn hls_command(binary_path: &str, env: Vec<(String, String)>) -> Result<Command> {
+    let windows = matches!(zed::current_platform(), (zed::Os::Windows, _));
+    let (name, env) = hls_invocation(binary_path, env, windows)?;
+    Ok(Command::new(name).envs(env))
+}
+
+fn hls_invocation(
+    binary_path: &str,
+    env: Vec<(String, String)>,
+    windows: bool,
+) -> Result<(String, Vec<(String, String)>)> {
+    let (directory, file_name) = binary_path
+        .rsplit_once(['/', '\\'])
+        .unwrap_or(("", binary_path));
+    let name = match windows {
+        true => file_name.strip_suffix(".exe").unwrap_or(file_name),
+        false => file_name,
+    };
+    if !HLS_BINARIES.contains(&name) {
+        return Err(format!(
+            "{binary_path} can't be run by the extension, which may only run {}",
+            HLS_BINARIES.join(" and ")
+        ));
+    }
+    if directory.is_empty() {
+        return Ok((name.to_string(), env));
+    }
+    let separator = if windows { ';' } else { ':' };
+    let search_path = match env.iter().find(|(key, _)| key == "PATH") {
+        Some((_, search_path)) => format!("{directory}{separator}{search_path}"),
+        None => directory.to_string(),
+    };
+    let env = merge_env(env, HashMap::from([("PATH".to_string(), search_path)]));
+    Ok((name.to_string(), env))
+}
+
+/// Adds HLS's top-level settings, like `formattingProvider`, from the
+/// bundled schema to a raw schema lacking them, since
+/// `vscode-extension-schema` only describes plugins.
+fn with_top_level_settings(mut raw_schema: serde_json::Value) -> serde_json::Value {
+    if let (Some(schema), serde_json::Value::Object(bundled)) =
+        (raw_schema.as_object_mut(), bundled_schema())
+    {
+        for (key, value) in bundled {
+            if !key.starts_with("haskell.plugin.") {
+                schema.entry(key).or_insert(value);
+            }
+        }
+    }
+    raw_schema
+}
+
+/// A `vscode-extension-schema` dump shipped with the extension, for HLS
+/// builds that can't produce one, plus HLS's top-level settings, which that
+/// command doesn't describe.
+fn bundled_schema() -> serde_json::Value {
+    serde_json::from_str(include_str!("hls-schema.json")).expect("hls-schema.json is valid JSON")
 }
 
 fn convert_to_zed_schema(raw_schema: &serde_json::Value) -> serde_json::Value {
@@ -124,6 +1231,25 @@ fn convert_to_zed_schema(raw_schema: &serde_json::Value) -> serde_json::Value {
     })
 }
 
+/// Adds the flat dotted form of every nested setting, like
+/// `plugin.hlint.globalOn`, to a converted schema, so settings copied from
+/// VSCode get completions too. They are expanded before reaching HLS.
+fn add_dotted_properties(schema: &mut serde_json::Value, raw_schema: &serde_json::Value) {
+    let (Some(properties), Some(raw_schema)) =
+        (schema["properties"].as_object_mut(), raw_schema.as_object())
+    else {
+        return;
+    };
+    for (key, value) in raw_schema {
+        let Some(key) = key.strip_prefix("haskell.") else {
+            continue;
+        };
+        if key.contains('.') {
+            properties.insert(key.to_string(), convert_leaf_schema(value));
+        }
+    }
+}
+
 fn insert_nested_property(
     properties: &mut serde_json::Map<String, serde_json::Value>,
     path: &[&str],
@@ -170,23 +1296,184 @@ fn convert_leaf_schema(leaf_value: &serde_json::Value) -> serde_json::Value {
     if let Some(desc) = leaf_obj.get("markdownDescription") {
         result.insert("markdownDescription".to_string(), desc.clone());
     }
+    // Zed only renders per-value documentation from `markdownEnumDescriptions`.
+    if let Some(descs) = leaf_obj
+        .get("markdownEnumDescriptions")
+        .or_else(|| leaf_obj.get("enumDescriptions"))
+    {
+        result.insert("markdownEnumDescriptions".to_string(), descs.clone());
+    }
+    // A deprecation message alone doesn't mark the option as deprecated.
+    if let Some(message) = leaf_obj
+        .get("deprecationMessage")
+        .or_else(|| leaf_obj.get("markdownDeprecationMessage"))
+    {
+        result.insert("deprecated".to_string(), serde_json::Value::Bool(true));
+        result.insert("deprecationMessage".to_string(), message.clone());
+    }
 
     for (key, value) in leaf_obj {
         match key.as_str() {
-            "default" | "type" | "enum" | "enumDescriptions" | "items" | "minimum" | "maximum"
-            | "anyOf" => {
+            "default" | "type" | "enum" | "minimum" | "maximum" | "exclusiveMinimum"
+            | "exclusiveMaximum" | "multipleOf" => {
                 result.insert(key.clone(), value.clone());
             }
-            "scope" | "description" => {}
+            "items" => {
+                result.insert(key.clone(), convert_leaf_schema(value));
+            }
+            "anyOf" => result.extend(convert_any_of(value)),
+            "scope"
+            | "description"
+            | "enumDescriptions"
+            | "markdownEnumDescriptions"
+            | "markdownDeprecationMessage" => {}
             _ => {
                 result.insert(key.clone(), value.clone());
             }
         }
     }
 
+    // HLS leaves out `items` for lists like `hlint.config.flags`, which
+    // keeps Zed from offering to edit them as lists.
+    if result.get("type").is_some_and(|ty| ty == "array") && !result.contains_key("items") {
+        let default_items = result
+            .get("default")
+            .and_then(|default| default.as_array())
+            .cloned()
+            .unwrap_or_default();
+        if default_items.iter().all(|item| item.is_string()) {
+            result.insert("items".to_string(), serde_json::json!({ "type": "string" }));
+        }
+    }
+
     serde_json::Value::Object(result)
 }
 
+/// Zed's settings editor doesn't render `anyOf`, so alternatives that only
+/// differ in their type (say, a number or `null`) are collapsed into a list
+/// of types, and enum alternatives are merged into a single enum. Anything
+/// more involved stays an `anyOf` of converted alternatives.
+fn convert_any_of(any_of: &serde_json::Value) -> serde_json::Map<String, serde_json::Value> {
+    let mut result = serde_json::Map::new();
+    let Some(alternatives) = any_of.as_array() else {
+        result.insert("anyOf".to_string(), any_of.clone());
+        return result;
+    };
+    let keep_any_of = || {
+        let alternatives = alternatives.iter().map(convert_leaf_schema).collect();
+        serde_json::Map::from_iter([("anyOf".to_string(), serde_json::Value::Array(alternatives))])
+    };
+
+    let mut types = Vec::new();
+    let mut enum_values = Vec::new();
+    let mut enum_descriptions = Some(Vec::new());
+    let mut free_types = Vec::new();
+    for alternative in alternatives {
+        let Some(alternative) = alternative.as_object() else {
+            return keep_any_of();
+        };
+        let is_simple = alternative.keys().all(|key| {
+            matches!(
+                key.as_str(),
+                "type"
+                    | "enum"
+                    | "const"
+                    | "enumDescriptions"
+                    | "markdownEnumDescriptions"
+                    | "description"
+                    | "markdownDescription"
+            )
+        });
+        if !is_simple {
+            return keep_any_of();
+        }
+
+        let alternative_types: Vec<serde_json::Value> = match alternative.get("type") {
+            Some(serde_json::Value::Array(alternative_types)) => alternative_types.clone(),
+            Some(ty) => vec![ty.clone()],
+            None => Vec::new(),
+        };
+        let description = alternative
+            .get("markdownDescription")
+            .or_else(|| alternative.get("description"));
+        if let Some(value) = alternative.get("const") {
+            enum_values.push(value.clone());
+            if let (Some(descriptions), Some(description)) = (&mut enum_descriptions, description) {
+                descriptions.push(description.clone());
+            } else {
+                enum_descriptions = None;
+            }
+        } else if let Some(values) = alternative.get("enum").and_then(|e| e.as_array()) {
+            enum_values.extend(values.iter().cloned());
+            match (
+                &mut enum_descriptions,
+                alternative
+                    .get("markdownEnumDescriptions")
+                    .or_else(|| alternative.get("enumDescriptions"))
+                    .and_then(|d| d.as_array()),
+            ) {
+                (Some(descriptions), Some(values)) => descriptions.extend(values.iter().cloned()),
+                _ => enum_descriptions = None,
+            }
+        } else if alternative_types.is_empty() {
+            return keep_any_of();
+        } else {
+            free_types.extend(alternative_types.iter().cloned());
+        }
+        for ty in alternative_types {
+            if !types.contains(&ty) {
+                types.push(ty);
+            }
+        }
+    }
+
+    if !enum_values.is_empty() {
+        // Unconstrained alternatives can only be merged into the enum if
+        // they allow a single value.
+        for ty in &free_types {
+            if ty != "null" {
+                return keep_any_of();
+            }
+            enum_values.push(serde_json::Value::Null);
+            if let Some(descriptions) = &mut enum_descriptions {
+                descriptions.push(serde_json::Value::String(String::new()));
+            }
+        }
+        result.insert("enum".to_string(), serde_json::Value::Array(enum_values));
+        if let Some(descriptions) = enum_descriptions {
+            result.insert(
+                "markdownEnumDescriptions".to_string(),
+                serde_json::Value::Array(descriptions),
+            );
+        }
+    }
+    if !types.is_empty() {
+        let ty = if types.len() == 1 {
+            types.remove(0)
+        } else {
+            serde_json::Value::Array(types)
+        };
+        result.insert("type".to_string(), ty);
+    }
+    result
+}
+
+/// Collects the `default` of every setting in a converted schema into a
+/// settings object of the same shape.
+fn default_settings(schema: &serde_json::Value) -> serde_json::Value {
+    let mut settings = serde_json::Map::new();
+    if let Some(properties) = schema["properties"].as_object() {
+        for (key, value) in properties {
+            if value.get("properties").is_some() {
+                settings.insert(key.clone(), default_settings(value));
+            } else if let Some(default) = value.get("default") {
+                settings.insert(key.clone(), default.clone());
+            }
+        }
+    }
+    serde_json::Value::Object(settings)
+}
+
 zed::register_extension!(HaskellExtension);
 
 #[cfg(test)]
@@ -261,4 +1548,396 @@ mod tests {
             serde_json::to_string_pretty(&result).unwrap()
         );
     }
+
+    #[test]
+    fn test_enum_descriptions_become_markdown_enum_descriptions() {
+        let input = serde_json::json!({
+            "haskell.formattingProvider": {
+                "default": "ormolu",
+                "description": "The formatter to use when formatting a document or range",
+                "enum": ["ormolu", "fourmolu", "none"],
+                "enumDescriptions": [
+                    "Use [ormolu](https://github.com/tweag/ormolu)",
+                    "Use [fourmolu](https://github.com/fourmolu/fourmolu)",
+                    "No formatting"
+                ],
+                "scope": "resource",
+                "type": "string"
+            }
+        });
+
+        let result = convert_to_zed_schema(&input);
+        let provider = &result["properties"]["formattingProvider"];
+
+        assert_eq!(provider["enum"][1], "fourmolu");
+        assert_eq!(
+            provider["markdownEnumDescriptions"][1],
+            "Use [fourmolu](https://github.com/fourmolu/fourmolu)"
+        );
+        assert!(provider.get("enumDescriptions").is_none());
+    }
+
+    #[test]
+    fn test_deprecated_settings_are_marked() {
+        let input = serde_json::json!({
+            "haskell.plugin.ghcide-completions.config.snippetsOn": {
+                "default": true,
+                "markdownDeprecationMessage": "Snippets are always enabled",
+                "scope": "resource",
+                "type": "boolean"
+            },
+            "haskell.plugin.eval.globalOn": {
+                "default": true,
+                "scope": "resource",
+                "type": "boolean"
+            }
+        });
+
+        let result = convert_to_zed_schema(&input);
+        let props = &result["properties"]["plugin"]["properties"];
+
+        let snippets_on =
+            &props["ghcide-completions"]["properties"]["config"]["properties"]["snippetsOn"];
+        assert_eq!(snippets_on["deprecated"], true);
+        assert_eq!(
+            snippets_on["deprecationMessage"],
+            "Snippets are always enabled"
+        );
+        assert!(snippets_on.get("markdownDeprecationMessage").is_none());
+
+        assert!(props["eval"]["properties"]["globalOn"]
+            .get("deprecated")
+            .is_none());
+    }
+
+    #[test]
+    fn test_default_settings() {
+        let input = serde_json::json!({
+            "haskell.formattingProvider": {
+                "default": "ormolu",
+                "scope": "resource",
+                "type": "string"
+            },
+            "haskell.plugin.eval.config.diff": {
+                "default": true,
+                "scope": "resource",
+                "type": "boolean"
+            },
+            "haskell.plugin.hlint.globalOn": {
+                "default": false,
+                "scope": "resource",
+                "type": "boolean"
+            },
+            "haskell.serverExecutablePath": {
+                "scope": "resource",
+                "type": "string"
+            }
+        });
+
+        let defaults = default_settings(&convert_to_zed_schema(&input));
+
+        assert_eq!(
+            defaults,
+            serde_json::json!({
+                "formattingProvider": "ormolu",
+                "plugin": {
+                    "eval": { "config": { "diff": true } },
+                    "hlint": { "globalOn": false }
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_bundled_schema() {
+        let schema = convert_to_zed_schema(&bundled_schema());
+        let plugins = &schema["properties"]["plugin"]["properties"];
+
+        assert_eq!(
+            plugins["hlint"]["properties"]["diagnosticsOn"]["type"],
+            "boolean"
+        );
+        assert_eq!(
+            plugins["ghcide-type-lenses"]["properties"]["config"]["properties"]["mode"]["default"],
+            "always"
+        );
+        for key in [
+            "cabalFormattingProvider",
+            "checkParents",
+            "checkProject",
+            "formattingProvider",
+            "maxCompletions",
+            "sessionLoading",
+        ] {
+            assert!(schema["properties"][key]["type"].is_string(), "{key}");
+        }
+    }
+
+    #[test]
+    fn test_merge_env() {
+        let shell_env = vec![
+            ("PATH".to_string(), "/usr/bin".to_string()),
+            ("STACK_YAML".to_string(), "stack.yaml".to_string()),
+        ];
+        let overrides = HashMap::from([
+            ("STACK_YAML".to_string(), "stack-ghc-9.8.yaml".to_string()),
+            ("GHC_CHARENC".to_string(), "UTF-8".to_string()),
+        ]);
+
+        assert_eq!(
+            merge_env(shell_env, overrides),
+            vec![
+                ("PATH".to_string(), "/usr/bin".to_string()),
+                ("STACK_YAML".to_string(), "stack-ghc-9.8.yaml".to_string()),
+                ("GHC_CHARENC".to_string(), "UTF-8".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merge_json_keeps_defaults() {
+        let mut options = default_initialization_options();
+        merge_json(
+            &mut options,
+            serde_json::json!({
+                "haskell": {
+                    "formattingProvider": "fourmolu",
+                    "plugin": {
+                        "cabal": { "diagnosticsOn": false },
+                        "hlint": { "globalOn": false }
+                    }
+                }
+            }),
+        );
+
+        assert_eq!(
+            options,
+            serde_json::json!({
+                "haskell": {
+                    "formattingProvider": "fourmolu",
+                    "maxCompletions": 40,
+                    "plugin": {
+                        "cabal": { "globalOn": true, "diagnosticsOn": false },
+                        "hlint": { "globalOn": false }
+                    }
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_any_of_leaves() {
+        let input = serde_json::json!({
+            "haskell.plugin.foo.config.limit": {
+                "anyOf": [{ "type": "integer" }, { "type": "null" }],
+                "default": null,
+                "description": "Maximum number of results, or no limit",
+                "scope": "resource"
+            },
+            "haskell.plugin.foo.config.mode": {
+                "anyOf": [
+                    {
+                        "enum": ["fast", "thorough"],
+                        "enumDescriptions": ["Quick check", "Full check"],
+                        "type": "string"
+                    },
+                    { "const": 0, "description": "Disabled", "type": "integer" },
+                    { "type": "null" }
+                ],
+                "default": "fast",
+                "scope": "resource"
+            },
+            "haskell.plugin.foo.config.target": {
+                "anyOf": [
+                    { "type": "string", "description": "A file" },
+                    {
+                        "type": "object",
+                        "properties": { "path": { "type": "string" } },
+                        "description": "A file and options"
+                    }
+                ],
+                "scope": "resource"
+            }
+        });
+
+        let result = convert_to_zed_schema(&input);
+        let config = &result["properties"]["plugin"]["properties"]["foo"]["properties"]["config"]
+            ["properties"];
+
+        let limit = &config["limit"];
+        assert_eq!(limit["type"], serde_json::json!(["integer", "null"]));
+        assert!(limit.get("anyOf").is_none());
+        assert!(limit.get("enum").is_none());
+
+        let mode = &config["mode"];
+        assert_eq!(
+            mode["type"],
+            serde_json::json!(["string", "integer", "null"])
+        );
+        assert_eq!(
+            mode["enum"],
+            serde_json::json!(["fast", "thorough", 0, null])
+        );
+        assert_eq!(
+            mode["markdownEnumDescriptions"],
+            serde_json::json!(["Quick check", "Full check", "Disabled", ""])
+        );
+
+        let target = &config["target"];
+        assert!(target.get("type").is_none());
+        assert_eq!(target["anyOf"][0]["markdownDescription"], "A file");
+        assert_eq!(
+            target["anyOf"][1]["markdownDescription"],
+            "A file and options"
+        );
+        assert!(target["anyOf"][1].get("description").is_none());
+    }
+
+    #[test]
+    fn test_array_settings() {
+        let input = serde_json::json!({
+            "haskell.plugin.hlint.config.flags": {
+                "default": [],
+                "markdownDescription": "Flags used by hlint",
+                "scope": "resource",
+                "type": "array"
+            },
+            "haskell.plugin.fourmolu.config.args": {
+                "default": ["--indentation", "2"],
+                "items": {
+                    "description": "An argument passed to fourmolu",
+                    "type": "string"
+                },
+                "scope": "resource",
+                "type": "array"
+            }
+        });
+
+        let result = convert_to_zed_schema(&input);
+        let plugins = &result["properties"]["plugin"]["properties"];
+
+        let flags = &plugins["hlint"]["properties"]["config"]["properties"]["flags"];
+        assert_eq!(flags["type"], "array");
+        assert_eq!(flags["items"], serde_json::json!({ "type": "string" }));
+        assert_eq!(flags["default"], serde_json::json!([]));
+
+        let args = &plugins["fourmolu"]["properties"]["config"]["properties"]["args"];
+        assert_eq!(args["default"], serde_json::json!(["--indentation", "2"]));
+        assert_eq!(
+            args["items"],
+            serde_json::json!({
+                "markdownDescription": "An argument passed to fourmolu",
+                "type": "string"
+            })
+        );
+    }
+
+    #[test]
+    fn test_numeric_constraints() {
+        let input = serde_json::json!({
+            "haskell.maxCompletions": {
+                "default": 40,
+                "exclusiveMinimum": 0,
+                "maximum": 1000,
+                "multipleOf": 1,
+                "scope": "resource",
+                "type": "integer"
+            },
+            "haskell.plugin.foo.config.ratio": {
+                "default": 0.5,
+                "exclusiveMaximum": 1,
+                "minimum": 0,
+                "multipleOf": 0.25,
+                "scope": "resource",
+                "type": "number"
+            }
+        });
+
+        let result = convert_to_zed_schema(&input);
+
+        let max_completions = &result["properties"]["maxCompletions"];
+        assert_eq!(max_completions["exclusiveMinimum"], 0);
+        assert_eq!(max_completions["maximum"], 1000);
+        assert_eq!(max_completions["multipleOf"], 1);
+
+        let ratio = &result["properties"]["plugin"]["properties"]["foo"]["properties"]["config"]
+            ["properties"]["ratio"];
+        assert_eq!(ratio["minimum"], 0);
+        assert_eq!(ratio["exclusiveMaximum"], 1);
+        assert_eq!(ratio["multipleOf"], 0.25);
+    }
+
+    #[test]
+    fn test_add_dotted_properties() {
+        let raw_schema = serde_json::json!({
+            "haskell.maxCompletions": {
+                "default": 40,
+                "type": "integer"
+            },
+            "haskell.plugin.hlint.globalOn": {
+                "default": true,
+                "description": "Enables hlint plugin",
+                "type": "boolean"
+            }
+        });
+        let mut schema = convert_to_zed_schema(&raw_schema);
+        add_dotted_properties(&mut schema, &raw_schema);
+
+        let properties = schema["properties"].as_object().unwrap();
+        assert_eq!(
+            properties.keys().collect::<Vec<_>>(),
+            vec!["maxCompletions", "plugin", "plugin.hlint.globalOn"]
+        );
+        assert_eq!(
+            properties["plugin.hlint.globalOn"],
+            properties["plugin"]["properties"]["hlint"]["properties"]["globalOn"]
+        );
+    }
+
+    #[test]
+    fn test_hsc_queries_match_haskell() {
+        assert_eq!(
+            include_str!("../languages/hsc/highlights.scm"),
+            include_str!("../languages/haskell/highlights.scm")
+        );
+        assert_eq!(
+            include_str!("../languages/hsc/indents.scm"),
+            include_str!("../languages/haskell/indents.scm")
+        );
+    }
+
+    #[test]
+    fn test_hls_invocation() {
+        let env = vec![("PATH".to_string(), "/usr/bin".to_string())];
+        assert_eq!(
+            hls_invocation(
+                "/home/me/.ghcup/bin/haskell-language-server-wrapper",
+                env,
+                false
+            ),
+            Ok((
+                "haskell-language-server-wrapper".to_string(),
+                vec![(
+                    "PATH".to_string(),
+                    "/home/me/.ghcup/bin:/usr/bin".to_string()
+                )]
+            ))
+        );
+        assert_eq!(
+            hls_invocation(
+                "C:\\ghcup\\bin\\haskell-language-server-wrapper.exe",
+                Vec::new(),
+                true
+            ),
+            Ok((
+                "haskell-language-server-wrapper".to_string(),
+                vec![("PATH".to_string(), "C:\\ghcup\\bin".to_string())]
+            ))
+        );
+        assert_eq!(
+            hls_invocation("haskell-language-server", Vec::new(), false),
+            Ok(("haskell-language-server".to_string(), Vec::new()))
+        );
+        assert!(hls_invocation("/usr/bin/python3", Vec::new(), false).is_err());
+    }
 }
@@ -0,0 +1,107 @@
+use zed_extension_api::{self as zed, Result};
+
+use crate::cabal_file::{self, Component};
+
+/// Generates an explicit `hie.yaml` for the package in the worktree root,
+/// with an entry for every component, like implicit-hie's `gen-hie`.
+///
+/// `gen-hie` only reads the current directory, which an extension can't
+/// set, so the components are read from the `.cabal` file instead, see
+/// [`cabal_file::read`]. Stack is used when there is a `stack.yaml` but no
+/// `cabal.project`.
+pub fn generate(worktree: &zed::Worktree, cabal_file: Option<&str>) -> Result<String> {
+    let (file_name, cabal_file) = cabal_file::read(worktree, cabal_file)?;
+    let stack = worktree.read_text_file("stack.yaml").is_ok()
+        && worktree.read_text_file("cabal.project").is_err();
+    let components =
+        cabal_file::components(&cabal_file, cabal_file::ghc_version(worktree).as_deref());
+    if components.is_empty() {
+        return Err(format!("{file_name} has no components"));
+    }
+    Ok(cradle(&components, stack))
+}
+
+/// Renders a multi-cradle with an entry for every source directory of
+/// `components`, and one for every `main-is`, which needn't be named after
+/// its module.
+fn cradle(components: &[Component], stack: bool) -> String {
+    let mut cradle = format!("cradle:\n  {}:\n", if stack { "stack" } else { "cabal" });
+    for component in components {
+        let target = if stack {
+            stack_target(&component.target)
+        } else {
+            component.target.clone()
+        };
+        let mut paths: Vec<String> = component
+            .source_dirs
+            .iter()
+            .map(|dir| source_path(dir, None))
+            .collect();
+        if let Some(main_is) = &component.main_is {
+            paths.extend(
+                component
+                    .source_dirs
+                    .iter()
+                    .map(|dir| source_path(dir, Some(main_is))),
+            );
+        }
+        for path in paths {
+            cradle.push_str(&format!(
+                "    - path: \"{path}\"\n      component: \"{target}\"\n\n"
+            ));
+        }
+    }
+    cradle.truncate(cradle.trim_end().len());
+    cradle.push('\n');
+    cradle
+}
+
+fn source_path(dir: &str, file: Option<&str>) -> String {
+    let dir = match dir.trim_end_matches('/') {
+        "." | "" => ".",
+        dir => dir.strip_prefix("./").unwrap_or(dir),
+    };
+    match (dir, file) {
+        (".", None) => "./".to_string(),
+        (".", Some(file)) => format!("./{file}"),
+        (dir, None) => format!("./{dir}"),
+        (dir, Some(file)) => format!("./{dir}/{file}"),
+    }
+}
+
+/// Stack names the main library `demo:lib` rather than `demo:lib:demo`.
+fn stack_target(target: &str) -> String {
+    match target.split(':').collect::<Vec<_>>()[..] {
+        [package, "lib", name] if package == name => format!("{package}:lib"),
+        _ => target.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cradle() {
+        let components = cabal_file::components(
+            "name: demo\n\
+             library\n  hs-source-dirs: src\n\
+             executable demo\n  main-is: Main.hs\n  hs-source-dirs: app\n\
+             test-suite spec\n  main-is: Spec.hs\n",
+            None,
+        );
+
+        assert_eq!(
+            cradle(&components, false),
+            "cradle:\n  cabal:\n\
+             \x20   - path: \"./src\"\n      component: \"demo:lib:demo\"\n\n\
+             \x20   - path: \"./app\"\n      component: \"demo:exe:demo\"\n\n\
+             \x20   - path: \"./app/Main.hs\"\n      component: \"demo:exe:demo\"\n\n\
+             \x20   - path: \"./\"\n      component: \"demo:test:spec\"\n\n\
+             \x20   - path: \"./Spec.hs\"\n      component: \"demo:test:spec\"\n"
+        );
+        assert!(cradle(&components, true).starts_with(
+            "cradle:\n  stack:\n    - path: \"./src\"\n      component: \"demo:lib\"\n"
+        ));
+    }
+}
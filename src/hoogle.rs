@@ -0,0 +1,254 @@
+use zed_extension_api::http_client::{HttpMethod, HttpRequest, RedirectPolicy};
+use zed_extension_api::process::{Command, Output};
+use zed_extension_api::{self as zed, Result};
+
+use crate::extension_log;
+
+/// How many results to ask Hoogle for.
+const COUNT: usize = 20;
+
+/// A Hoogle result.
+#[derive(Debug, PartialEq)]
+pub struct Hit {
+    /// The declaration as Hoogle shows it, e.g. `fromMaybe :: a -> Maybe a -> a`.
+    pub item: String,
+    pub module: Option<String>,
+    pub package: Option<String>,
+    pub url: String,
+    pub docs: String,
+}
+
+/// Searches the local Hoogle database, generating it from the installed
+/// packages if there is none, or hoogle.haskell.org if Hoogle isn't
+/// installed.
+pub fn search(query: &str, worktree: Option<&zed::Worktree>) -> Result<Vec<Hit>> {
+    let Some(worktree) = worktree.filter(|worktree| worktree.which("hoogle").is_some()) else {
+        return search_online(query);
+    };
+    let output = local_search(query, worktree)?;
+    if output.status == Some(0) {
+        return parse(&output.stdout);
+    }
+    // Hoogle asks to run `hoogle generate` when there is no database.
+    let error = String::from_utf8_lossy(&output.stderr);
+    if !error.contains("hoogle generate") {
+        return Err(format!("hoogle failed: {error}"));
+    }
+
+    extension_log::log(
+        "generating a local Hoogle database with `hoogle generate --local`".to_string(),
+    );
+    let generate = Command::new("hoogle")
+        .args(["generate", "--local"])
+        .envs(worktree.shell_env())
+        .output()?;
+    if generate.status != Some(0) {
+        return Err(format!(
+            "failed to generate a Hoogle database: {}",
+            String::from_utf8_lossy(&generate.stderr)
+        ));
+    }
+    let output = local_search(query, worktree)?;
+    if output.status != Some(0) {
+        return Err(format!(
+            "hoogle failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    parse(&output.stdout)
+}
+
+fn local_search(query: &str, worktree: &zed::Worktree) -> Result<Output> {
+    Command::new("hoogle")
+        .args(["search", "--json", &format!("--count={COUNT}"), "--", query])
+        .envs(worktree.shell_env())
+        .output()
+}
+
+fn search_online(query: &str) -> Result<Vec<Hit>> {
+    let url = format!(
+        "https://hoogle.haskell.org/?mode=json&count={COUNT}&hoogle={}",
+        percent_encode(query)
+    );
+    let request = HttpRequest::builder()
+        .method(HttpMethod::Get)
+        .url(url)
+        .redirect_policy(RedirectPolicy::FollowAll)
+        .build()?;
+    parse(&request.fetch()?.body)
+}
+
+/// Parses Hoogle's JSON output, which is the same locally and online.
+pub fn parse(json: &[u8]) -> Result<Vec<Hit>> {
+    let hits: Vec<serde_json::Value> =
+        serde_json::from_slice(json).map_err(|e| format!("failed to parse Hoogle results: {e}"))?;
+    Ok(hits
+        .iter()
+        .filter_map(|hit| {
+            let name = |key: &str| hit[key]["name"].as_str().map(ToString::to_string);
+            Some(Hit {
+                item: strip_html(hit["item"].as_str()?),
+                module: name("module"),
+                package: name("package"),
+                url: hit["url"].as_str()?.to_string(),
+                docs: strip_html(hit["docs"].as_str().unwrap_or_default())
+                    .trim()
+                    .to_string(),
+            })
+        })
+        .collect())
+}
+
+/// Returns the import bringing `hit` into scope, if it is a declaration or
+/// a module.
+pub fn import(hit: &Hit) -> Option<String> {
+    if let Some(module) = hit.item.strip_prefix("module ") {
+        return Some(format!("import {module}"));
+    }
+    let module = hit.module.as_deref()?;
+    let name = match hit.item.split_once(" :: ") {
+        Some((name, _)) => name.trim(),
+        None => {
+            let mut words = hit.item.split_whitespace();
+            match words.next()? {
+                "data" | "newtype" | "type" | "class" => {
+                    words.find(|word| word.starts_with(|c: char| c.is_uppercase() || c == '('))?
+                }
+                "package" => return None,
+                name => name,
+            }
+        }
+    };
+    Some(format!("import {module} ({name})"))
+}
+
+/// Renders hits as Markdown, returning the text and the range and label of
+/// every hit.
+pub fn render(query: &str, hits: &[Hit]) -> (String, Vec<(std::ops::Range<usize>, String)>) {
+    if hits.is_empty() {
+        return (format!("Hoogle found nothing for `{query}`.\n"), Vec::new());
+    }
+    let mut text = String::new();
+    let mut sections = Vec::new();
+    for hit in hits {
+        let start = text.len();
+        text.push_str(&format!("```haskell\n{}\n```\n", hit.item));
+        let location: Vec<&str> = [hit.package.as_deref(), hit.module.as_deref()]
+            .into_iter()
+            .flatten()
+            .collect();
+        if !location.is_empty() {
+            text.push_str(&format!("{} ", location.join(" ")));
+        }
+        text.push_str(&format!("([docs]({}))\n", hit.url));
+        if let Some(import) = import(hit) {
+            text.push_str(&format!("```haskell\n{import}\n```\n"));
+        }
+        if !hit.docs.is_empty() {
+            text.push_str(&format!("\n{}\n", hit.docs));
+        }
+        text.push('\n');
+        sections.push((start..text.len(), hit.item.clone()));
+    }
+    (text, sections)
+}
+
+/// Removes the markup Hoogle puts around names and in documentation.
+fn strip_html(html: &str) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+fn percent_encode(query: &str) -> String {
+    query
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_import() {
+        let hits = parse(
+            serde_json::json!([
+                {
+                    "url": "https://hackage.haskell.org/package/base/docs/Data-Maybe.html#v:fromMaybe",
+                    "module": { "name": "Data.Maybe", "url": "https://hackage.haskell.org/package/base/docs/Data-Maybe.html" },
+                    "package": { "name": "base", "url": "https://hackage.haskell.org/package/base" },
+                    "item": "<span class=name><0>fromMaybe</0></span> :: a -&gt; Maybe a -&gt; a",
+                    "type": "",
+                    "docs": "The <a>fromMaybe</a> function takes a default value and a <a>Maybe</a> value.\n"
+                },
+                {
+                    "url": "https://hackage.haskell.org/package/base/docs/Data-Functor.html#v:-60--36--62-",
+                    "module": { "name": "Data.Functor", "url": "" },
+                    "package": { "name": "base", "url": "" },
+                    "item": "<span class=name><0>(&lt;$&gt;)</0></span> :: Functor f =&gt; (a -&gt; b) -&gt; f a -&gt; f b",
+                    "type": "",
+                    "docs": ""
+                },
+                {
+                    "url": "https://hackage.haskell.org/package/containers/docs/Data-Map-Strict.html",
+                    "module": {},
+                    "package": { "name": "containers", "url": "" },
+                    "item": "<b>module</b> Data.Map.Strict",
+                    "type": "module",
+                    "docs": ""
+                },
+                {
+                    "url": "https://hackage.haskell.org/package/text/docs/Data-Text.html#t:Text",
+                    "module": { "name": "Data.Text", "url": "" },
+                    "package": { "name": "text", "url": "" },
+                    "item": "<b>data</b> <span class=name><0>Text</0></span>",
+                    "type": "",
+                    "docs": ""
+                }
+            ])
+            .to_string()
+            .as_bytes(),
+        )
+        .unwrap();
+
+        assert_eq!(hits[0].item, "fromMaybe :: a -> Maybe a -> a");
+        assert_eq!(
+            hits[0].docs,
+            "The fromMaybe function takes a default value and a Maybe value."
+        );
+        let imports: Vec<Option<String>> = hits.iter().map(import).collect();
+        assert_eq!(
+            imports,
+            vec![
+                Some("import Data.Maybe (fromMaybe)".to_string()),
+                Some("import Data.Functor ((<$>))".to_string()),
+                Some("import Data.Map.Strict".to_string()),
+                Some("import Data.Text (Text)".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_percent_encode() {
+        assert_eq!(percent_encode("a -> Maybe a"), "a%20-%3E%20Maybe%20a");
+    }
+}
@@ -0,0 +1,568 @@
+use std::ops::Range;
+
+use zed_extension_api::lsp::{CompletionKind, SymbolKind};
+use zed_extension_api::{CodeLabel, CodeLabelSpan};
+
+/// A code label, kept independent of the extension API so label generation
+/// can be tested.
+///
+/// All ranges are byte offsets, as Zed expects, and have to fall on
+/// character boundaries; see [`Label::is_valid`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Label {
+    /// Haskell source the label is highlighted as.
+    pub code: String,
+    pub spans: Vec<Span>,
+    /// The part of the displayed text that is matched against the query.
+    pub filter_range: Range<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Span {
+    /// A range of `code`, shown with its syntax highlighting.
+    Code(Range<usize>),
+    /// Text shown as is, with an optional highlight name.
+    Literal(String, Option<String>),
+}
+
+impl Label {
+    /// Returns the text shown to the user.
+    pub fn text(&self) -> String {
+        self.spans
+            .iter()
+            .map(|span| match span {
+                Span::Code(range) => self.code.get(range.clone()).unwrap_or_default(),
+                Span::Literal(text, _) => text.as_str(),
+            })
+            .collect()
+    }
+
+    /// Returns whether every range is in bounds and on character
+    /// boundaries, so slicing with it can't panic or split a character.
+    pub fn is_valid(&self) -> bool {
+        let code_spans_valid = self.spans.iter().all(|span| match span {
+            Span::Code(range) => self.code.get(range.clone()).is_some(),
+            Span::Literal(..) => true,
+        });
+        code_spans_valid && self.text().get(self.filter_range.clone()).is_some()
+    }
+}
+
+/// Returns the byte range `text` occupies when it follows `prefix`.
+fn range_after(prefix: &str, text: &str) -> Range<usize> {
+    prefix.len()..prefix.len() + text.len()
+}
+
+/// Returns whether `name` is the name of a type, rather than a value.
+fn is_type_name(name: &str) -> bool {
+    unqualified(name).starts_with(char::is_uppercase)
+}
+
+/// Labels a completion with the type HLS puts in its detail, as in
+/// `foldr :: (a -> b -> b) -> b -> t a -> b`, followed by the module it
+/// comes from, dimmed.
+pub fn completion_label(
+    name: &str,
+    kind: CompletionKind,
+    detail: Option<&str>,
+    module: Option<&str>,
+) -> Option<Label> {
+    if matches!(kind, CompletionKind::Keyword) {
+        return pragma_label(name);
+    }
+
+    let ty = detail
+        .and_then(|detail| detail.trim().strip_prefix("::"))
+        .map(str::trim)
+        .filter(|ty| !ty.is_empty());
+    let module = module.map(str::trim).filter(|module| !module.is_empty());
+    if ty.is_none() && module.is_none() {
+        return None;
+    }
+
+    // Constructors are highlighted as part of a GADT-style declaration,
+    // which is the only place they can have a signature.
+    let decl = match kind {
+        CompletionKind::Function
+        | CompletionKind::Method
+        | CompletionKind::Variable
+        | CompletionKind::Field
+        | CompletionKind::Value
+        | CompletionKind::Property => "",
+        CompletionKind::Constructor if ty.is_some() => "data A where ",
+        CompletionKind::Constructor => "data A = ",
+        _ => return None,
+    };
+    let (open, close) = parentheses(name);
+    let code = match ty {
+        Some(ty) => format!("{decl}{open}{name}{close} :: {ty}"),
+        None => format!("{decl}{open}{name}{close}"),
+    };
+    let mut spans = vec![Span::Code(decl.len()..code.len())];
+    if let Some(module) = module {
+        spans.push(Span::Literal(
+            format!("  {module}"),
+            Some("comment".to_string()),
+        ));
+    }
+    let name_range = range_after(open, name);
+    Some(Label {
+        spans,
+        filter_range: name_range.end - unqualified(name).len()..name_range.end,
+        code,
+    })
+    .filter(Label::is_valid)
+}
+
+/// Labels the completion of a pragma or GHC extension, like
+/// `OverloadedStrings`, filtering on the extension name only.
+fn pragma_label(name: &str) -> Option<Label> {
+    let name_start = name.rfind(' ').map_or(0, |space| space + 1);
+    Some(Label {
+        code: String::new(),
+        spans: vec![Span::Literal(name.to_string(), Some("keyword".to_string()))],
+        filter_range: name_start..name.len(),
+    })
+    .filter(Label::is_valid)
+}
+
+/// Returns the parentheses `name` has to be wrapped in to be valid Haskell
+/// in a declaration. They aren't part of the name that's filtered on.
+fn parentheses(name: &str) -> (&'static str, &'static str) {
+    if is_operator(name) {
+        ("(", ")")
+    } else {
+        ("", "")
+    }
+}
+
+/// Returns `name` without its module qualifier, e.g. `!` for `Map.!`.
+fn unqualified(name: &str) -> &str {
+    let mut rest = name;
+    while rest.starts_with(char::is_uppercase) {
+        let module_end = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '\''))
+            .unwrap_or(rest.len());
+        match rest[module_end..].strip_prefix('.') {
+            Some(unqualified) if !unqualified.is_empty() => rest = unqualified,
+            _ => break,
+        }
+    }
+    rest
+}
+
+/// Returns whether `name` is an operator, like `<$>` or `Map.!`.
+fn is_operator(name: &str) -> bool {
+    unqualified(name)
+        .chars()
+        .next()
+        .is_some_and(|c| !(c.is_alphanumeric() || c == '_' || c == '('))
+}
+
+impl From<Label> for CodeLabel {
+    fn from(label: Label) -> Self {
+        CodeLabel {
+            spans: label
+                .spans
+                .into_iter()
+                .map(|span| match span {
+                    Span::Code(range) => CodeLabelSpan::code_range(range),
+                    Span::Literal(text, highlight) => CodeLabelSpan::literal(text, highlight),
+                })
+                .collect(),
+            filter_range: label.filter_range.into(),
+            code: label.code,
+        }
+    }
+}
+
+pub fn symbol_label(name: &str, kind: SymbolKind) -> Option<Label> {
+    // HLS names pattern synonyms `pattern Name`, whatever kind it reports.
+    let (name, (decl, suffix, show_decl)) = match name.strip_prefix("pattern ") {
+        Some(name) => (name, ("pattern ", " :: T", true)),
+        None => (name, symbol_decl(name, kind)?),
+    };
+
+    let (open, close) = parentheses(name);
+    let code = format!("{decl}{open}{name}{close}{suffix}");
+    let name_range = range_after(&format!("{decl}{open}"), name);
+    let display_start = if show_decl { 0 } else { decl.len() };
+    let display_range = display_start..name_range.end + close.len();
+    // Module names are dotted too, but all of them is the name.
+    let filter_len = match kind {
+        SymbolKind::Module => name.len(),
+        _ => unqualified(name).len(),
+    };
+    let filter_range = name_range.end - filter_len - display_start..name_range.end - display_start;
+
+    Some(Label {
+        spans: vec![Span::Code(display_range)],
+        filter_range,
+        code,
+    })
+    .filter(Label::is_valid)
+}
+
+/// Returns the declaration a kind of symbol is highlighted as, split
+/// around the name, and whether the declaration keyword is shown.
+fn symbol_decl(name: &str, kind: SymbolKind) -> Option<(&'static str, &'static str, bool)> {
+    Some(match kind {
+        SymbolKind::Struct => ("data ", " = A", true),
+        SymbolKind::Module => ("module ", " where", true),
+        SymbolKind::Class | SymbolKind::Interface => ("class ", "", true),
+        SymbolKind::Constructor => ("data A = ", "", false),
+        SymbolKind::Field => ("data A = A { ", " :: T }", false),
+        // HLS reports type synonyms as type parameters, and type and data
+        // families as functions, which only their capitalized name tells
+        // apart from actual functions.
+        SymbolKind::TypeParameter => ("type ", " = T", true),
+        SymbolKind::Function if is_type_name(name) => ("type family ", "", true),
+        // Symbols don't carry their type signature, so functions get a
+        // placeholder one, which is enough to highlight the name.
+        SymbolKind::Variable | SymbolKind::Function => ("", " :: T", false),
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Renders a label as its code, displayed text, and filter text, one
+    /// per line.
+    fn snapshot(label: Option<Label>) -> String {
+        let Some(label) = label else {
+            return "<none>".to_string();
+        };
+        let text = label.text();
+        let filter = text.get(label.filter_range.clone()).unwrap_or("<invalid>");
+        format!("{}\n{text}\n{filter}", label.code)
+    }
+
+    #[test]
+    fn test_symbol_labels() {
+        assert_eq!(
+            snapshot(symbol_label("Maybe", SymbolKind::Struct)),
+            "data Maybe = A\ndata Maybe\nMaybe"
+        );
+        assert_eq!(
+            snapshot(symbol_label("Just", SymbolKind::Constructor)),
+            "data A = Just\nJust\nJust"
+        );
+        assert_eq!(
+            snapshot(symbol_label("fromMaybe", SymbolKind::Variable)),
+            "fromMaybe :: T\nfromMaybe\nfromMaybe"
+        );
+        assert_eq!(
+            snapshot(symbol_label("Data.Maybe", SymbolKind::Package)),
+            "<none>"
+        );
+    }
+
+    #[test]
+    fn test_module_symbol_labels() {
+        assert_eq!(
+            snapshot(symbol_label("Data.Map.Strict", SymbolKind::Module)),
+            "module Data.Map.Strict where\nmodule Data.Map.Strict\nData.Map.Strict"
+        );
+    }
+
+    #[test]
+    fn test_class_symbol_labels() {
+        assert_eq!(
+            snapshot(symbol_label("Functor", SymbolKind::Interface)),
+            "class Functor\nclass Functor\nFunctor"
+        );
+        assert_eq!(
+            snapshot(symbol_label("MonadState", SymbolKind::Class)),
+            "class MonadState\nclass MonadState\nMonadState"
+        );
+    }
+
+    #[test]
+    fn test_type_synonym_and_family_symbol_labels() {
+        assert_eq!(
+            snapshot(symbol_label("FilePath", SymbolKind::TypeParameter)),
+            "type FilePath = T\ntype FilePath\nFilePath"
+        );
+        assert_eq!(
+            snapshot(symbol_label("Elem", SymbolKind::Function)),
+            "type family Elem\ntype family Elem\nElem"
+        );
+    }
+
+    #[test]
+    fn test_field_symbol_labels() {
+        assert_eq!(
+            snapshot(symbol_label("runReader", SymbolKind::Field)),
+            "data A = A { runReader :: T }\nrunReader\nrunReader"
+        );
+    }
+
+    #[test]
+    fn test_pattern_synonym_symbol_labels() {
+        assert_eq!(
+            snapshot(symbol_label("pattern Snoc", SymbolKind::Function)),
+            "pattern Snoc :: T\npattern Snoc\nSnoc"
+        );
+        assert_eq!(
+            snapshot(symbol_label("pattern :>", SymbolKind::Constructor)),
+            "pattern (:>) :: T\npattern (:>)\n:>"
+        );
+    }
+
+    #[test]
+    fn test_function_symbol_labels() {
+        assert_eq!(
+            snapshot(symbol_label("mapMaybe", SymbolKind::Function)),
+            "mapMaybe :: T\nmapMaybe\nmapMaybe"
+        );
+    }
+
+    #[test]
+    fn test_operator_symbol_labels() {
+        assert_eq!(
+            snapshot(symbol_label("<$>", SymbolKind::Variable)),
+            "(<$>) :: T\n(<$>)\n<$>"
+        );
+        assert_eq!(
+            snapshot(symbol_label(":|", SymbolKind::Constructor)),
+            "data A = (:|)\n(:|)\n:|"
+        );
+        assert_eq!(
+            snapshot(symbol_label(":+:", SymbolKind::Struct)),
+            "data (:+:) = A\ndata (:+:)\n:+:"
+        );
+        assert_eq!(
+            snapshot(symbol_label("Map.!", SymbolKind::Function)),
+            "(Map.!) :: T\n(Map.!)\n!"
+        );
+    }
+
+    #[test]
+    fn test_unicode_symbol_labels() {
+        assert_eq!(
+            snapshot(symbol_label("∘", SymbolKind::Variable)),
+            "(∘) :: T\n(∘)\n∘"
+        );
+        assert_eq!(
+            snapshot(symbol_label("Größe", SymbolKind::Struct)),
+            "data Größe = A\ndata Größe\nGröße"
+        );
+    }
+
+    #[test]
+    fn test_unicode_ranges_fall_on_char_boundaries() {
+        for name in ["∘", "⊕", "λx", "Ωmega", "Größe.Maß", "𝔽", "a∷b"] {
+            for kind in [
+                SymbolKind::Struct,
+                SymbolKind::Constructor,
+                SymbolKind::Variable,
+                SymbolKind::Function,
+                SymbolKind::Interface,
+                SymbolKind::Module,
+                SymbolKind::TypeParameter,
+                SymbolKind::Field,
+            ] {
+                let label = symbol_label(name, kind).unwrap();
+                assert!(label.is_valid());
+                assert!(label.text().contains(name));
+                assert!(name.ends_with(&label.text()[label.filter_range.clone()]));
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_ranges_are_detected() {
+        // `∘` is three bytes long, so a one-byte range splits it.
+        let label = Label {
+            code: "∘ :: T".to_string(),
+            spans: vec![Span::Code(0..1)],
+            filter_range: 0..1,
+        };
+        assert!(!label.is_valid());
+
+        let label = Label {
+            code: "∘ :: T".to_string(),
+            spans: vec![Span::Code(0..3)],
+            filter_range: 0..4,
+        };
+        assert!(!label.is_valid());
+    }
+
+    #[test]
+    fn test_completion_labels() {
+        assert_eq!(
+            snapshot(completion_label(
+                "foldr",
+                CompletionKind::Function,
+                Some(":: (a -> b -> b) -> b -> t a -> b"),
+                None
+            )),
+            "foldr :: (a -> b -> b) -> b -> t a -> b\n\
+             foldr :: (a -> b -> b) -> b -> t a -> b\n\
+             foldr"
+        );
+        assert_eq!(
+            snapshot(completion_label(
+                "pi",
+                CompletionKind::Variable,
+                Some(":: Floating a => a"),
+                None
+            )),
+            "pi :: Floating a => a\npi :: Floating a => a\npi"
+        );
+        assert_eq!(
+            snapshot(completion_label(
+                "foldr",
+                CompletionKind::Function,
+                None,
+                None
+            )),
+            "<none>"
+        );
+        assert_eq!(
+            snapshot(completion_label(
+                "Data.List",
+                CompletionKind::Module,
+                Some(":: T"),
+                Some("Data.List")
+            )),
+            "<none>"
+        );
+    }
+
+    #[test]
+    fn test_completion_labels_with_module() {
+        assert_eq!(
+            snapshot(completion_label(
+                "foldl'",
+                CompletionKind::Function,
+                Some(":: (b -> a -> b) -> b -> t a -> b"),
+                Some("Data.List")
+            )),
+            "foldl' :: (b -> a -> b) -> b -> t a -> b\n\
+             foldl' :: (b -> a -> b) -> b -> t a -> b  Data.List\n\
+             foldl'"
+        );
+        assert_eq!(
+            snapshot(completion_label(
+                "foldl'",
+                CompletionKind::Function,
+                None,
+                Some("Data.Foldable")
+            )),
+            "foldl'\nfoldl'  Data.Foldable\nfoldl'"
+        );
+    }
+
+    #[test]
+    fn test_constructor_completion_labels() {
+        assert_eq!(
+            snapshot(completion_label(
+                "Just",
+                CompletionKind::Constructor,
+                Some(":: a -> Maybe a"),
+                None
+            )),
+            "data A where Just :: a -> Maybe a\nJust :: a -> Maybe a\nJust"
+        );
+        assert_eq!(
+            snapshot(completion_label(
+                "Nothing",
+                CompletionKind::Constructor,
+                None,
+                Some("GHC.Maybe")
+            )),
+            "data A = Nothing\nNothing  GHC.Maybe\nNothing"
+        );
+    }
+
+    #[test]
+    fn test_pragma_completion_labels() {
+        assert_eq!(
+            snapshot(completion_label(
+                "OverloadedStrings",
+                CompletionKind::Keyword,
+                None,
+                None
+            )),
+            "\nOverloadedStrings\nOverloadedStrings"
+        );
+        assert_eq!(
+            snapshot(completion_label(
+                "LANGUAGE DerivingVia",
+                CompletionKind::Keyword,
+                Some(":: T"),
+                None
+            )),
+            "\nLANGUAGE DerivingVia\nDerivingVia"
+        );
+    }
+
+    #[test]
+    fn test_operator_completion_labels() {
+        assert_eq!(
+            snapshot(completion_label(
+                "<$>",
+                CompletionKind::Function,
+                Some(":: Functor f => (a -> b) -> f a -> f b"),
+                None
+            )),
+            "(<$>) :: Functor f => (a -> b) -> f a -> f b\n\
+             (<$>) :: Functor f => (a -> b) -> f a -> f b\n\
+             <$>"
+        );
+        assert_eq!(
+            snapshot(completion_label(
+                ":|",
+                CompletionKind::Constructor,
+                Some(":: a -> [a] -> NonEmpty a"),
+                None
+            )),
+            "data A where (:|) :: a -> [a] -> NonEmpty a\n\
+             (:|) :: a -> [a] -> NonEmpty a\n\
+             :|"
+        );
+    }
+
+    #[test]
+    fn test_qualified_symbol_labels() {
+        assert_eq!(
+            snapshot(symbol_label("Map.insertWith", SymbolKind::Variable)),
+            "Map.insertWith :: T\nMap.insertWith\ninsertWith"
+        );
+        assert_eq!(
+            snapshot(symbol_label("Map.Map", SymbolKind::Struct)),
+            "data Map.Map = A\ndata Map.Map\nMap"
+        );
+        assert_eq!(
+            snapshot(symbol_label("Data.Map", SymbolKind::Module)),
+            "module Data.Map where\nmodule Data.Map\nData.Map"
+        );
+    }
+
+    #[test]
+    fn test_qualified_completion_labels() {
+        assert_eq!(
+            snapshot(completion_label(
+                "Map.insert",
+                CompletionKind::Function,
+                Some(":: Ord k => k -> a -> Map k a -> Map k a"),
+                None
+            )),
+            "Map.insert :: Ord k => k -> a -> Map k a -> Map k a\n\
+             Map.insert :: Ord k => k -> a -> Map k a -> Map k a\n\
+             insert"
+        );
+        assert_eq!(
+            snapshot(completion_label(
+                "Seq.:<|",
+                CompletionKind::Constructor,
+                None,
+                Some("Data.Sequence")
+            )),
+            "data A = (Seq.:<|)\n(Seq.:<|)  Data.Sequence\n:<|"
+        );
+    }
+}
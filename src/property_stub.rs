@@ -0,0 +1,372 @@
+/// Property testing library to generate stubs for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Framework {
+    QuickCheck,
+    Hedgehog,
+}
+
+impl Framework {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "quickcheck" => Some(Self::QuickCheck),
+            "hedgehog" => Some(Self::Hedgehog),
+            _ => None,
+        }
+    }
+}
+
+/// A parsed Haskell type, as far as stub generation cares about it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Type {
+    Var(String),
+    Con(String, Vec<Type>),
+    List(Box<Type>),
+    Tuple(Vec<Type>),
+    Fun(Box<Type>, Box<Type>),
+}
+
+impl Type {
+    /// Renders the type with every type variable replaced by `Int`, since
+    /// properties have to be run at a concrete type.
+    fn monomorphic(&self) -> String {
+        match self {
+            Type::Var(_) => "Int".to_string(),
+            Type::Con(name, args) => {
+                let mut rendered = name.clone();
+                for arg in args {
+                    let arg_str = arg.monomorphic();
+                    if matches!(arg, Type::Con(_, args) if !args.is_empty())
+                        || matches!(arg, Type::Fun(..))
+                    {
+                        rendered.push_str(&format!(" ({arg_str})"));
+                    } else {
+                        rendered.push_str(&format!(" {arg_str}"));
+                    }
+                }
+                rendered
+            }
+            Type::List(elem) => format!("[{}]", elem.monomorphic()),
+            Type::Tuple(elems) => {
+                let elems: Vec<String> = elems.iter().map(Type::monomorphic).collect();
+                format!("({})", elems.join(", "))
+            }
+            Type::Fun(arg, result) => {
+                let arg_str = arg.monomorphic();
+                if matches!(**arg, Type::Fun(..)) {
+                    format!("({arg_str}) -> {}", result.monomorphic())
+                } else {
+                    format!("{arg_str} -> {}", result.monomorphic())
+                }
+            }
+        }
+    }
+
+    /// Returns a Hedgehog generator for the type, falling back to the
+    /// type's QuickCheck `Arbitrary` instance.
+    fn hedgehog_gen(&self) -> String {
+        const RANGE: &str = "(Range.linear 0 100)";
+        match self {
+            Type::Var(_) => format!("Gen.int {RANGE}"),
+            Type::Con(name, args) => match (name.as_str(), args.as_slice()) {
+                ("Int", []) => format!("Gen.int {RANGE}"),
+                ("Integer", []) => format!("Gen.integral {RANGE}"),
+                ("Word", []) => format!("Gen.word {RANGE}"),
+                ("Double", []) => "Gen.double (Range.linearFrac 0 100)".to_string(),
+                ("Float", []) => "Gen.float (Range.linearFrac 0 100)".to_string(),
+                ("Bool", []) => "Gen.bool".to_string(),
+                ("Char", []) => "Gen.unicode".to_string(),
+                ("String", []) => format!("Gen.string {RANGE} Gen.unicode"),
+                ("Text" | "T.Text", []) => format!("Gen.text {RANGE} Gen.unicode"),
+                ("Maybe", [arg]) => format!("Gen.maybe ({})", arg.hedgehog_gen()),
+                _ => "HQC.arbitrary".to_string(),
+            },
+            Type::List(elem) => format!("Gen.list {RANGE} ({})", elem.hedgehog_gen()),
+            Type::Tuple(elems) => {
+                let constructor = ",".repeat(elems.len() - 1);
+                let gens: Vec<String> = elems
+                    .iter()
+                    .map(|elem| format!("({})", elem.hedgehog_gen()))
+                    .collect();
+                format!("({constructor}) <$> {}", gens.join(" <*> "))
+            }
+            Type::Fun(..) => "HQC.arbitrary".to_string(),
+        }
+    }
+}
+
+const HEDGEHOG_IMPORTS: &str = "\
+import Hedgehog
+import qualified Hedgehog.Gen as Gen
+import qualified Hedgehog.Gen.QuickCheck as HQC
+import qualified Hedgehog.Range as Range
+";
+
+/// Generates a property stub for the function with the given signature,
+/// e.g. `reverse :: [a] -> [a]`.
+pub fn generate(signature: &str, framework: Framework) -> Result<String, String> {
+    let (name, ty) = signature
+        .split_once("::")
+        .ok_or("expected a type signature like `f :: Int -> Bool`")?;
+    let name = name.trim();
+    let function = if name.starts_with(|c: char| c.is_alphabetic() || c == '_') {
+        name.to_string()
+    } else {
+        // Operators are applied in prefix form.
+        format!("({})", name.trim_start_matches('(').trim_end_matches(')'))
+    };
+    let property_name = if function.starts_with('(') {
+        "prop_operator".to_string()
+    } else {
+        format!("prop_{function}")
+    };
+
+    let args = parse_arguments(ty)?;
+    let vars: Vec<String> = (1..=args.len()).map(|i| format!("x{i}")).collect();
+    let call = std::iter::once(function.as_str())
+        .chain(vars.iter().map(String::as_str))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let stub = match framework {
+        Framework::QuickCheck => {
+            let mut signature: Vec<String> = args
+                .iter()
+                .map(|arg| match arg {
+                    Type::Fun(..) => format!("({})", arg.monomorphic()),
+                    _ => arg.monomorphic(),
+                })
+                .collect();
+            signature.push("Property".to_string());
+            let lhs = std::iter::once(property_name.as_str())
+                .chain(vars.iter().map(String::as_str))
+                .collect::<Vec<_>>()
+                .join(" ");
+            format!(
+                "{property_name} :: {}\n{lhs} =\n  {call} === undefined\n",
+                signature.join(" -> ")
+            )
+        }
+        Framework::Hedgehog => {
+            let mut stub = format!(
+                "{HEDGEHOG_IMPORTS}\n{property_name} :: Property\n{property_name} = property $ do\n"
+            );
+            for (var, arg) in vars.iter().zip(&args) {
+                stub.push_str(&format!("  {var} <- forAll $ {}\n", arg.hedgehog_gen()));
+            }
+            stub.push_str(&format!("  {call} === undefined\n"));
+            stub
+        }
+    };
+    Ok(stub)
+}
+
+/// Returns the argument types of a function type, ignoring any `forall`
+/// and class context.
+fn parse_arguments(ty: &str) -> Result<Vec<Type>, String> {
+    let mut tokens = tokenize(ty);
+    if tokens.first().is_some_and(|t| t == "forall" || t == "∀") {
+        let dot = tokens
+            .iter()
+            .position(|t| t == ".")
+            .ok_or("unterminated forall")?;
+        tokens.drain(..=dot);
+    }
+    let mut depth = 0;
+    let mut context_end = None;
+    for (i, token) in tokens.iter().enumerate() {
+        match token.as_str() {
+            "(" | "[" => depth += 1,
+            ")" | "]" => depth -= 1,
+            "=>" | "⇒" if depth == 0 => context_end = Some(i),
+            _ => {}
+        }
+    }
+    if let Some(end) = context_end {
+        tokens.drain(..=end);
+    }
+
+    let mut parser = Parser { tokens, pos: 0 };
+    let mut ty = parser.parse_type()?;
+    if parser.pos != parser.tokens.len() {
+        return Err(format!(
+            "unexpected `{}` in type",
+            parser.tokens[parser.pos]
+        ));
+    }
+
+    let mut args = Vec::new();
+    while let Type::Fun(arg, result) = ty {
+        args.push(*arg);
+        ty = *result;
+    }
+    Ok(args)
+}
+
+fn tokenize(ty: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut chars = ty.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '(' | ')' | '[' | ']' | ',' | '→' | '⇒' | '∀' => tokens.push(c.to_string()),
+            '-' if chars.peek() == Some(&'>') => {
+                chars.next();
+                tokens.push("->".to_string());
+            }
+            '=' if chars.peek() == Some(&'>') => {
+                chars.next();
+                tokens.push("=>".to_string());
+            }
+            c if c.is_alphanumeric() || c == '_' => {
+                let mut ident = c.to_string();
+                while let Some(c) =
+                    chars.next_if(|&c| c.is_alphanumeric() || matches!(c, '_' | '\'' | '.'))
+                {
+                    ident.push(c);
+                }
+                // A trailing dot ends a `forall a b.` binder.
+                if let Some(ident) = ident.strip_suffix('.') {
+                    tokens.push(ident.to_string());
+                    tokens.push(".".to_string());
+                } else {
+                    tokens.push(ident);
+                }
+            }
+            c => tokens.push(c.to_string()),
+        }
+    }
+    tokens
+}
+
+struct Parser {
+    tokens: Vec<String>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.pos).map(String::as_str)
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), String> {
+        if self.peek() == Some(token) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(format!("expected `{token}` in type"))
+        }
+    }
+
+    fn parse_type(&mut self) -> Result<Type, String> {
+        let arg = self.parse_application()?;
+        if matches!(self.peek(), Some("->" | "→")) {
+            self.pos += 1;
+            let result = self.parse_type()?;
+            return Ok(Type::Fun(Box::new(arg), Box::new(result)));
+        }
+        Ok(arg)
+    }
+
+    fn parse_application(&mut self) -> Result<Type, String> {
+        let head = self.parse_atom()?;
+        let mut args = Vec::new();
+        while matches!(self.peek(), Some(t) if t == "(" || t == "[" || is_identifier(t)) {
+            args.push(self.parse_atom()?);
+        }
+        match head {
+            Type::Con(name, _) => Ok(Type::Con(name, args)),
+            // Applied type variables (`m a`) can't be generated generically.
+            Type::Var(name) if !args.is_empty() => Ok(Type::Con(name, args)),
+            head => Ok(head),
+        }
+    }
+
+    fn parse_atom(&mut self) -> Result<Type, String> {
+        match self.peek() {
+            Some("[") => {
+                self.pos += 1;
+                let elem = self.parse_type()?;
+                self.expect("]")?;
+                Ok(Type::List(Box::new(elem)))
+            }
+            Some("(") => {
+                self.pos += 1;
+                if self.peek() == Some(")") {
+                    self.pos += 1;
+                    return Ok(Type::Con("()".to_string(), Vec::new()));
+                }
+                let mut elems = vec![self.parse_type()?];
+                while self.peek() == Some(",") {
+                    self.pos += 1;
+                    elems.push(self.parse_type()?);
+                }
+                self.expect(")")?;
+                if elems.len() == 1 {
+                    Ok(elems.remove(0))
+                } else {
+                    Ok(Type::Tuple(elems))
+                }
+            }
+            Some(t) if is_identifier(t) => {
+                let name = t.to_string();
+                self.pos += 1;
+                if name.starts_with(char::is_uppercase) {
+                    Ok(Type::Con(name, Vec::new()))
+                } else {
+                    Ok(Type::Var(name))
+                }
+            }
+            Some(t) => Err(format!("unexpected `{t}` in type")),
+            None => Err("unexpected end of type".to_string()),
+        }
+    }
+}
+
+fn is_identifier(token: &str) -> bool {
+    token.starts_with(|c: char| c.is_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quickcheck_stub() {
+        let stub = generate(
+            "insertWith :: Ord k => (v -> v -> v) -> k -> v -> Map k v -> Map k v",
+            Framework::QuickCheck,
+        )
+        .unwrap();
+
+        assert_eq!(
+            stub,
+            "prop_insertWith :: (Int -> Int -> Int) -> Int -> Int -> Map Int Int -> Property\n\
+             prop_insertWith x1 x2 x3 x4 =\n  \
+             insertWith x1 x2 x3 x4 === undefined\n"
+        );
+    }
+
+    #[test]
+    fn test_hedgehog_stub() {
+        let stub = generate(
+            "lookup :: forall a. Eq a => a -> [(a, String)] -> Maybe Bool -> Config -> Maybe String",
+            Framework::Hedgehog,
+        )
+        .unwrap();
+
+        assert!(stub.contains("prop_lookup :: Property\nprop_lookup = property $ do\n"));
+        assert!(stub.contains("  x1 <- forAll $ Gen.int (Range.linear 0 100)\n"));
+        assert!(stub.contains(
+            "  x2 <- forAll $ Gen.list (Range.linear 0 100) ((,) <$> (Gen.int (Range.linear 0 100)) <*> (Gen.string (Range.linear 0 100) Gen.unicode))\n"
+        ));
+        assert!(stub.contains("  x3 <- forAll $ Gen.maybe (Gen.bool)\n"));
+        assert!(stub.contains("  x4 <- forAll $ HQC.arbitrary\n"));
+        assert!(stub.ends_with("  lookup x1 x2 x3 x4 === undefined\n"));
+    }
+
+    #[test]
+    fn test_invalid_signature() {
+        assert!(generate("reverse", Framework::QuickCheck).is_err());
+        assert!(generate("reverse :: [a -> [a]", Framework::QuickCheck).is_err());
+    }
+}
@@ -0,0 +1,83 @@
+use std::fs;
+use std::path::Path;
+
+/// File in the extension's work directory holding raw schema output.
+const CACHE_FILE: &str = "hls-schema-cache.json";
+
+/// Returns the cached `vscode-extension-schema` output for `binary_path`, if
+/// it was stored for the same `version`.
+///
+/// The binary itself lives outside the extension's sandbox and can't be
+/// inspected, so the versions it and the GHC it delegates to report tell
+/// whether it was replaced.
+pub fn load(binary_path: &str, version: &str) -> Option<String> {
+    load_from(Path::new(CACHE_FILE), binary_path, version)
+}
+
+/// Stores the schema output of `binary_path` at `version`. Failures are
+/// ignored, since the cache is only an optimization.
+pub fn store(binary_path: &str, version: &str, schema: &str) {
+    store_in(Path::new(CACHE_FILE), binary_path, version, schema)
+}
+
+fn load_from(cache_file: &Path, binary_path: &str, version: &str) -> Option<String> {
+    let cache = read_cache(cache_file);
+    let entry = cache.get(binary_path)?;
+    if entry["version"].as_str()? != version {
+        return None;
+    }
+    entry["schema"].as_str().map(str::to_string)
+}
+
+fn store_in(cache_file: &Path, binary_path: &str, version: &str, schema: &str) {
+    let mut cache = read_cache(cache_file);
+    if let Some(entries) = cache.as_object_mut() {
+        entries.insert(
+            binary_path.to_string(),
+            serde_json::json!({
+                "version": version,
+                "schema": schema,
+            }),
+        );
+    }
+    fs::write(cache_file, cache.to_string()).ok();
+}
+
+fn read_cache(cache_file: &Path) -> serde_json::Value {
+    fs::read_to_string(cache_file)
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .filter(serde_json::Value::is_object)
+        .unwrap_or_else(|| serde_json::json!({}))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_invalidated_when_version_changes() {
+        let cache_file =
+            std::env::temp_dir().join(format!("hls-schema-cache-{}.json", std::process::id()));
+        fs::remove_file(&cache_file).ok();
+
+        assert!(load_from(&cache_file, "/ghcup/bin/hls", "2.9.0.1").is_none());
+        store_in(&cache_file, "/ghcup/bin/hls", "2.9.0.1", "{}");
+        store_in(&cache_file, "/other/hls", "2.10.0.0", "{\"other\": {}}");
+
+        assert_eq!(
+            load_from(&cache_file, "/ghcup/bin/hls", "2.9.0.1").as_deref(),
+            Some("{}")
+        );
+        assert_eq!(
+            load_from(&cache_file, "/other/hls", "2.10.0.0").as_deref(),
+            Some("{\"other\": {}}")
+        );
+        assert!(load_from(&cache_file, "/ghcup/bin/hls", "2.10.0.0").is_none());
+        assert!(load_from(&cache_file, "/usr/bin/hls", "2.9.0.1").is_none());
+
+        fs::write(&cache_file, "not json").unwrap();
+        assert!(load_from(&cache_file, "/ghcup/bin/hls", "2.9.0.1").is_none());
+        fs::remove_file(&cache_file).ok();
+    }
+}
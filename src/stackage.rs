@@ -0,0 +1,270 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use zed_extension_api::http_client::{HttpMethod, HttpRequest, RedirectPolicy};
+
+/// The parts of a `stack.yaml` the upgrade assistant looks at.
+#[derive(Debug, PartialEq, Eq)]
+pub struct StackYaml {
+    pub resolver: String,
+    /// `(package, version)` for every plain `name-version` extra dep.
+    pub extra_deps: Vec<(String, String)>,
+}
+
+/// The result of comparing a project's extra deps against a snapshot.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct UpgradePlan {
+    /// Extra deps the new snapshot already provides at the same or a newer version.
+    pub unnecessary: Vec<String>,
+    /// Project dependencies the old snapshot provided but the new one dropped.
+    pub newly_required: Vec<String>,
+}
+
+/// Returns the name of the latest LTS snapshot, e.g. `lts-22.44`.
+pub fn latest_lts() -> Result<String, String> {
+    let snapshots: serde_json::Value =
+        serde_json::from_slice(&fetch("https://www.stackage.org/download/snapshots.json")?)
+            .map_err(|e| format!("failed to parse Stackage snapshot list: {e}"))?;
+    snapshots["lts"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| "Stackage didn't report a latest LTS".to_string())
+}
+
+/// Returns the package versions pinned by `snapshot`.
+pub fn snapshot_packages(snapshot: &str) -> Result<BTreeMap<String, String>, String> {
+    let body = fetch(&format!("https://www.stackage.org/{snapshot}/cabal.config"))?;
+    Ok(parse_cabal_config(&String::from_utf8_lossy(&body)))
+}
+
+fn fetch(url: &str) -> Result<Vec<u8>, String> {
+    let request = HttpRequest::builder()
+        .method(HttpMethod::Get)
+        .url(url)
+        .redirect_policy(RedirectPolicy::FollowAll)
+        .build()?;
+    Ok(request.fetch()?.body)
+}
+
+pub fn parse_stack_yaml(contents: &str) -> Result<StackYaml, String> {
+    let mut resolver = None;
+    let mut extra_deps = Vec::new();
+    let mut in_extra_deps = false;
+
+    for line in contents.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let top_level = !line.starts_with(char::is_whitespace);
+        if top_level && !trimmed.starts_with('-') {
+            in_extra_deps = trimmed == "extra-deps:";
+            if let Some(value) = trimmed
+                .strip_prefix("resolver:")
+                .or_else(|| trimmed.strip_prefix("snapshot:"))
+            {
+                resolver = Some(unquote(value).to_string());
+            }
+            continue;
+        }
+        if in_extra_deps {
+            // Only plain `name-version` entries; git and path deps are left alone.
+            if let Some(dep) = trimmed.strip_prefix("- ") {
+                let dep = unquote(dep);
+                let dep = dep.split('@').next().unwrap_or(dep);
+                if let Some(package) = split_package_version(dep) {
+                    extra_deps.push(package);
+                }
+            }
+        }
+    }
+
+    Ok(StackYaml {
+        resolver: resolver.ok_or("stack.yaml doesn't specify a resolver")?,
+        extra_deps,
+    })
+}
+
+/// Parses the `constraints:` of a Stackage `cabal.config`.
+fn parse_cabal_config(contents: &str) -> BTreeMap<String, String> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim().trim_start_matches("constraints:").trim();
+            let (name, version) = line.split_once("==")?;
+            Some((
+                name.trim().to_string(),
+                version.trim().trim_end_matches(',').to_string(),
+            ))
+        })
+        .collect()
+}
+
+/// Returns the names listed under any `dependencies:` key of a `package.yaml`.
+pub fn package_yaml_dependencies(contents: &str) -> Vec<String> {
+    let mut dependencies = Vec::new();
+    let mut dependencies_indent = None;
+
+    for line in contents.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let indent = line.len() - line.trim_start().len();
+        if trimmed == "dependencies:" {
+            dependencies_indent = Some(indent);
+            continue;
+        }
+        match dependencies_indent {
+            Some(parent) if indent > parent || (indent == parent && trimmed.starts_with('-')) => {
+                if let Some(dep) = trimmed.strip_prefix("- ") {
+                    if let Some(name) = unquote(dep).split_whitespace().next() {
+                        dependencies.push(name.to_string());
+                    }
+                }
+            }
+            _ => dependencies_indent = None,
+        }
+    }
+    dependencies
+}
+
+pub fn plan_upgrade(
+    extra_deps: &[(String, String)],
+    dependencies: &[String],
+    old_snapshot: &BTreeMap<String, String>,
+    new_snapshot: &BTreeMap<String, String>,
+) -> UpgradePlan {
+    let unnecessary = extra_deps
+        .iter()
+        .filter(|(name, version)| {
+            new_snapshot.get(name).is_some_and(|snapshot_version| {
+                compare_versions(snapshot_version, version) != Ordering::Less
+            })
+        })
+        .map(|(name, version)| format!("{name}-{version}"))
+        .collect();
+    let newly_required = dependencies
+        .iter()
+        .filter(|name| old_snapshot.contains_key(*name) && !new_snapshot.contains_key(*name))
+        .filter(|name| !extra_deps.iter().any(|(dep, _)| dep == *name))
+        .cloned()
+        .collect();
+    UpgradePlan {
+        unnecessary,
+        newly_required,
+    }
+}
+
+/// Returns `contents` with the resolver bumped and the given extra deps removed.
+pub fn apply_upgrade(contents: &str, new_resolver: &str, unnecessary: &[String]) -> String {
+    let mut output = String::with_capacity(contents.len());
+    for line in contents.lines() {
+        let trimmed = line.trim();
+        if let Some(key) = ["resolver:", "snapshot:"]
+            .into_iter()
+            .find(|key| !line.starts_with(char::is_whitespace) && trimmed.starts_with(key))
+        {
+            output.push_str(&format!("{key} {new_resolver}\n"));
+            continue;
+        }
+        if let Some(dep) = trimmed.strip_prefix("- ") {
+            let dep = unquote(dep);
+            let dep = dep.split('@').next().unwrap_or(dep);
+            if unnecessary.iter().any(|unnecessary| unnecessary == dep) {
+                continue;
+            }
+        }
+        output.push_str(line);
+        output.push('\n');
+    }
+    output
+}
+
+fn unquote(value: &str) -> &str {
+    let value = value.split(" #").next().unwrap_or(value).trim();
+    value.trim_matches(|c| c == '"' || c == '\'')
+}
+
+/// Splits `text-2.0.2` into `("text", "2.0.2")`.
+fn split_package_version(dep: &str) -> Option<(String, String)> {
+    let (name, version) = dep.rsplit_once('-')?;
+    if version.is_empty() || !version.chars().all(|c| c.is_ascii_digit() || c == '.') {
+        return None;
+    }
+    Some((name.to_string(), version.to_string()))
+}
+
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    let parse = |v: &str| -> Vec<u64> { v.split('.').filter_map(|p| p.parse().ok()).collect() };
+    parse(a).cmp(&parse(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STACK_YAML: &str = "\
+resolver: lts-21.25 # GHC 9.4
+
+packages:
+- .
+
+extra-deps:
+- text-2.1
+- aeson-2.2.1.0@sha256:deadbeef,1234
+- git: https://github.com/example/foo
+  commit: abc123
+- \"brick-2.3\"
+";
+
+    #[test]
+    fn test_parse_stack_yaml() {
+        let stack_yaml = parse_stack_yaml(STACK_YAML).unwrap();
+        assert_eq!(stack_yaml.resolver, "lts-21.25");
+        assert_eq!(
+            stack_yaml.extra_deps,
+            vec![
+                ("text".to_string(), "2.1".to_string()),
+                ("aeson".to_string(), "2.2.1.0".to_string()),
+                ("brick".to_string(), "2.3".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_plan_and_apply_upgrade() {
+        let old_snapshot = parse_cabal_config(
+            "constraints: aeson ==2.1.2.1,\n             text ==2.0.2,\n             vty ==5.38,\n",
+        );
+        let new_snapshot = parse_cabal_config(
+            "constraints: aeson ==2.2.3.0,\n             text ==2.0.2,\n             brick ==2.3,\n",
+        );
+        let dependencies = package_yaml_dependencies(
+            "name: demo\ndependencies:\n  - base >= 4 && < 5\n  - vty\n  - text\nexecutables:\n  demo:\n    main: Main.hs\n",
+        );
+        assert_eq!(dependencies, vec!["base", "vty", "text"]);
+
+        let stack_yaml = parse_stack_yaml(STACK_YAML).unwrap();
+        let plan = plan_upgrade(
+            &stack_yaml.extra_deps,
+            &dependencies,
+            &old_snapshot,
+            &new_snapshot,
+        );
+        assert_eq!(
+            plan,
+            UpgradePlan {
+                unnecessary: vec!["aeson-2.2.1.0".to_string(), "brick-2.3".to_string()],
+                newly_required: vec!["vty".to_string()],
+            }
+        );
+
+        let upgraded = apply_upgrade(STACK_YAML, "lts-22.44", &plan.unnecessary);
+        assert!(upgraded.starts_with("resolver: lts-22.44\n"));
+        assert!(upgraded.contains("- text-2.1\n"));
+        assert!(!upgraded.contains("aeson"));
+        assert!(!upgraded.contains("brick"));
+        assert!(upgraded.contains("- git: https://github.com/example/foo\n"));
+    }
+}
@@ -0,0 +1,194 @@
+use zed_extension_api::process::Command;
+use zed_extension_api::{self as zed, Result};
+
+use crate::cabal_file;
+
+/// Something stan observed in the code, with the inspection that found it.
+#[derive(Debug, PartialEq)]
+pub struct Observation {
+    pub inspection_id: String,
+    pub name: Option<String>,
+    pub severity: Option<String>,
+    pub file: String,
+    pub line: u64,
+    pub column: u64,
+}
+
+/// Runs stan on the worktree, with its `.stan.toml` and `.cabal` file if
+/// there are, returning what it observed. `cabal_file` names the `.cabal`
+/// file like for [`cabal_file::read`].
+///
+/// stan reads the `.hie` files GHC writes to `.hie` with
+/// `-fwrite-ide-info -hiedir=.hie`.
+pub fn run(worktree: &zed::Worktree, cabal_file: Option<&str>) -> Result<Vec<Observation>> {
+    if worktree.which("stan").is_none() {
+        return Err("stan is not installed, see https://github.com/kowainik/stan".to_string());
+    }
+    // Commands can't be given a working directory, so point stan at the
+    // worktree's files instead.
+    let root_path = worktree.root_path();
+    let mut args = vec!["--hiedir".to_string(), format!("{root_path}/.hie")];
+    match cabal_file::read(worktree, cabal_file) {
+        Ok((file_name, _)) => {
+            args.extend([
+                "--cabal-file-path".to_string(),
+                format!("{root_path}/{file_name}"),
+            ]);
+        }
+        // stan finds `.cabal` files itself, unless it was asked for one that
+        // doesn't exist.
+        Err(e) if cabal_file.is_some() => return Err(e),
+        Err(_) => {}
+    }
+    if worktree.read_text_file(".stan.toml").is_ok() {
+        args.extend([
+            "--config-file".to_string(),
+            format!("{root_path}/.stan.toml"),
+        ]);
+    }
+    args.push("--json-output".to_string());
+    let output = Command::new("stan")
+        .args(args)
+        .envs(worktree.shell_env())
+        .output()?;
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).map_err(|_| {
+        format!(
+            "stan failed: {}",
+            String::from_utf8_lossy(if output.stderr.is_empty() {
+                &output.stdout
+            } else {
+                &output.stderr
+            })
+        )
+    })?;
+    Ok(observations(&report))
+}
+
+/// Reads the observations of a `stan --json-output` report, looking up the
+/// name and severity of their inspections.
+pub fn observations(report: &serde_json::Value) -> Vec<Observation> {
+    let inspections = report["inspections"].as_array();
+    let Some(observations) = report["observations"].as_array() else {
+        return Vec::new();
+    };
+    observations
+        .iter()
+        .filter_map(|observation| {
+            let inspection_id = observation["inspectionId"].as_str()?;
+            let inspection = inspections.and_then(|inspections| {
+                inspections
+                    .iter()
+                    .find(|inspection| inspection["id"] == inspection_id)
+            });
+            let field = |name: &str| {
+                inspection
+                    .and_then(|inspection| inspection[name].as_str())
+                    .map(ToString::to_string)
+            };
+            Some(Observation {
+                inspection_id: inspection_id.to_string(),
+                name: field("name"),
+                severity: field("severity"),
+                file: observation["file"].as_str()?.to_string(),
+                line: observation["startLine"].as_u64()?,
+                column: observation["startCol"].as_u64()?,
+            })
+        })
+        .collect()
+}
+
+/// Returns the documentation of an inspection like `STAN-0203` on Hackage,
+/// where inspections are grouped into modules by their number.
+pub fn docs_url(inspection_id: &str) -> Option<String> {
+    let number = inspection_id.strip_prefix("STAN-")?;
+    let module = match number.get(..2)? {
+        "00" => "Partial",
+        "01" => "Infinite",
+        "02" => "AntiPattern",
+        "03" => "Style",
+        _ => return None,
+    };
+    Some(format!(
+        "https://hackage.haskell.org/package/stan/docs/Stan-Inspection-{module}.html#v:stan{number}"
+    ))
+}
+
+/// Renders observations as a Markdown list per file.
+pub fn report(observations: &[Observation]) -> String {
+    if observations.is_empty() {
+        return "stan observed nothing.\n".to_string();
+    }
+    let mut report = String::new();
+    let mut current_file = None;
+    for observation in observations {
+        if current_file != Some(&observation.file) {
+            if current_file.is_some() {
+                report.push('\n');
+            }
+            report.push_str(&format!("{}:\n", observation.file));
+            current_file = Some(&observation.file);
+        }
+        let inspection = match docs_url(&observation.inspection_id) {
+            Some(url) => format!("[{}]({url})", observation.inspection_id),
+            None => observation.inspection_id.clone(),
+        };
+        report.push_str(&format!("- {inspection}"));
+        if let Some(severity) = &observation.severity {
+            report.push_str(&format!(" ({severity})"));
+        }
+        if let Some(name) = &observation.name {
+            report.push_str(&format!(" {name}"));
+        }
+        report.push_str(&format!(
+            " at {}:{}:{}\n",
+            observation.file, observation.line, observation.column
+        ));
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_observations_report() {
+        let report_json = serde_json::json!({
+            "inspections": [
+                {
+                    "id": "STAN-0001",
+                    "name": "Partial: base/head",
+                    "severity": "Warning",
+                    "category": ["Partial", "List"]
+                }
+            ],
+            "observations": [
+                {
+                    "id": "OBS-STAN-0001-abc",
+                    "inspectionId": "STAN-0001",
+                    "file": "src/Demo.hs",
+                    "startLine": 12,
+                    "startCol": 9,
+                    "endLine": 12,
+                    "endCol": 13
+                },
+                {
+                    "id": "OBS-STAN-0999-def",
+                    "inspectionId": "STAN-0999",
+                    "file": "src/Demo.hs",
+                    "startLine": 20,
+                    "startCol": 1
+                }
+            ]
+        });
+        let observations = observations(&report_json);
+
+        assert_eq!(
+            report(&observations),
+            "src/Demo.hs:\n\
+             - [STAN-0001](https://hackage.haskell.org/package/stan/docs/Stan-Inspection-Partial.html#v:stan0001) \
+             (Warning) Partial: base/head at src/Demo.hs:12:9\n\
+             - STAN-0999 at src/Demo.hs:20:1\n"
+        );
+    }
+}
@@ -0,0 +1,255 @@
+/// Checks `settings` against a converted HLS schema, returning a warning for
+/// every unknown key, wrongly typed value, or value outside an enum.
+pub fn validate(schema: &serde_json::Value, settings: &serde_json::Value) -> Vec<String> {
+    let mut warnings = Vec::new();
+    validate_at(schema, settings, "", &mut warnings);
+    warnings
+}
+
+fn validate_at(
+    schema: &serde_json::Value,
+    value: &serde_json::Value,
+    path: &str,
+    warnings: &mut Vec<String>,
+) {
+    if let Some(properties) = schema.get("properties").and_then(|p| p.as_object()) {
+        let Some(object) = value.as_object() else {
+            warnings.push(format!("`{path}` should be an object"));
+            return;
+        };
+        for (key, value) in object {
+            let child_path = if path.is_empty() {
+                key.clone()
+            } else {
+                format!("{path}.{key}")
+            };
+            match properties.get(key) {
+                Some(child_schema) => validate_at(child_schema, value, &child_path, warnings),
+                None if is_plugin_toggle(path, key) => {}
+                // Only plugin settings are described exhaustively; newer
+                // HLS versions may have top-level settings we don't know.
+                None if path.is_empty() => {}
+                None => warnings.push(format!("unknown setting `{child_path}`")),
+            }
+        }
+        return;
+    }
+
+    if let Some(expected) = schema.get("type") {
+        let types: Vec<&str> = match expected {
+            serde_json::Value::String(ty) => vec![ty.as_str()],
+            serde_json::Value::Array(types) => types.iter().filter_map(|t| t.as_str()).collect(),
+            _ => Vec::new(),
+        };
+        if !types.is_empty() && !types.iter().any(|ty| has_type(value, ty)) {
+            warnings.push(format!(
+                "`{path}` should be of type {}, found {value}",
+                types.join(" or ")
+            ));
+            return;
+        }
+    }
+
+    if let (Some(items), Some(elements)) = (schema.get("items"), value.as_array()) {
+        for (i, element) in elements.iter().enumerate() {
+            validate_at(items, element, &format!("{path}[{i}]"), warnings);
+        }
+    }
+
+    if let Some(number) = value.as_f64() {
+        let bound = |key: &str| schema.get(key).and_then(|bound| bound.as_f64());
+        let violated = [
+            ("minimum", bound("minimum").filter(|min| number < *min)),
+            ("maximum", bound("maximum").filter(|max| number > *max)),
+            (
+                "exclusiveMinimum",
+                bound("exclusiveMinimum").filter(|min| number <= *min),
+            ),
+            (
+                "exclusiveMaximum",
+                bound("exclusiveMaximum").filter(|max| number >= *max),
+            ),
+            (
+                "multipleOf",
+                bound("multipleOf").filter(|step| *step > 0.0 && (number / step).fract() != 0.0),
+            ),
+        ];
+        for (constraint, bound) in violated {
+            if let Some(bound) = bound {
+                warnings.push(format!(
+                    "`{path}` violates {constraint} {bound}, found {value}"
+                ));
+            }
+        }
+    }
+
+    if let Some(allowed) = schema.get("enum").and_then(|e| e.as_array()) {
+        if !allowed.contains(value) {
+            let allowed: Vec<String> = allowed.iter().map(|v| v.to_string()).collect();
+            warnings.push(format!(
+                "`{path}` should be one of {}, found {value}",
+                allowed.join(", ")
+            ));
+        }
+    }
+}
+
+/// Returns the path of every key in `settings` that the converted HLS
+/// schema doesn't know about. Unlike `validate`, this includes top-level
+/// settings, so the schema must be completed with `with_top_level_settings`.
+pub fn unknown_settings(schema: &serde_json::Value, settings: &serde_json::Value) -> Vec<String> {
+    let mut unknown = Vec::new();
+    unknown_settings_at(schema, settings, "", &mut unknown);
+    unknown
+}
+
+fn unknown_settings_at(
+    schema: &serde_json::Value,
+    value: &serde_json::Value,
+    path: &str,
+    unknown: &mut Vec<String>,
+) {
+    let (Some(properties), Some(object)) = (
+        schema.get("properties").and_then(|p| p.as_object()),
+        value.as_object(),
+    ) else {
+        return;
+    };
+    for (key, value) in object {
+        let child_path = if path.is_empty() {
+            key.clone()
+        } else {
+            format!("{path}.{key}")
+        };
+        match properties.get(key) {
+            Some(child_schema) => unknown_settings_at(child_schema, value, &child_path, unknown),
+            None if is_plugin_toggle(path, key) => {}
+            None => unknown.push(child_path),
+        }
+    }
+}
+
+/// Returns whether `key` is `globalOn` of the plugin at `path`, which HLS
+/// reads for every plugin, although the schema only lists it for some.
+fn is_plugin_toggle(path: &str, key: &str) -> bool {
+    key == "globalOn"
+        && path
+            .strip_prefix("plugin.")
+            .is_some_and(|plugin| !plugin.contains('.'))
+}
+
+fn has_type(value: &serde_json::Value, ty: &str) -> bool {
+    match ty {
+        "null" => value.is_null(),
+        "boolean" => value.is_boolean(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "array" => value.is_array(),
+        "object" => value.is_object(),
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An excerpt of `haskell-language-server vscode-extension-schema`,
+    /// completed with the top-level settings the extension adds.
+    fn schema() -> serde_json::Value {
+        crate::convert_to_zed_schema(&crate::with_top_level_settings(serde_json::json!({
+            "haskell.plugin.hlint.codeActionsOn": {
+                "default": true,
+                "description": "Enables hlint code actions",
+                "scope": "resource",
+                "type": "boolean"
+            },
+            "haskell.plugin.hlint.config.flags": {
+                "default": [],
+                "markdownDescription": "Flags used by hlint",
+                "scope": "resource",
+                "type": "array"
+            },
+            "haskell.plugin.hlint.diagnosticsOn": {
+                "default": true,
+                "description": "Enables hlint diagnostics",
+                "scope": "resource",
+                "type": "boolean"
+            },
+            "haskell.plugin.ormolu.config.external": {
+                "default": false,
+                "markdownDescription": "Call out to an external \"ormolu\" executable, rather than using the bundled library",
+                "scope": "resource",
+                "type": "boolean"
+            }
+        })))
+    }
+
+    #[test]
+    fn test_validate_settings() {
+        let schema = schema();
+
+        let settings = serde_json::json!({
+            "formattingProvider": "fourmolu",
+            "cabalFormattingProvider": "cabal-fmt",
+            "checkProject": false,
+            "maxCompletions": 40,
+            "someFutureSetting": 1,
+            "plugin": {
+                "hlint": { "diagnosticsOn": false },
+                "ormolu": { "globalOn": false },
+                "hlitn": { "globalOn": false }
+            }
+        });
+        assert_eq!(
+            validate(&schema, &settings),
+            vec!["unknown setting `plugin.hlitn`"]
+        );
+
+        let settings = serde_json::json!({
+            "formattingProvider": true,
+            "checkParents": "OnSave",
+            "maxCompletions": 4.5,
+            "plugin": {
+                "hlint": { "codeActionsOn": "yes", "config": { "flags": "--no-summary" } }
+            }
+        });
+        assert_eq!(
+            validate(&schema, &settings),
+            vec![
+                "`checkParents` should be one of \"NeverCheck\", \"CheckOnSave\", \"AlwaysCheck\", found \"OnSave\"",
+                "`formattingProvider` should be of type string, found true",
+                "`maxCompletions` should be of type integer, found 4.5",
+                "`plugin.hlint.codeActionsOn` should be of type boolean, found \"yes\"",
+                "`plugin.hlint.config.flags` should be of type array, found \"--no-summary\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unknown_settings() {
+        let settings = serde_json::json!({
+            "formattingProvider": "ormolu",
+            "cabalFormattingProvider": "cabal-gild",
+            "checkParents": "CheckOnSave",
+            "checkProject": false,
+            "maxCompletions": 40,
+            "sessionLoading": "multipleComponents",
+            "formatingProvider": "ormolu",
+            "plugin": {
+                "hlint": { "globalOn": false, "diagnosticsOn": "yes", "diagnosticOn": false },
+                "ormolu": { "globalOn": false },
+                "hlitn": { "globalOn": false }
+            }
+        });
+        assert_eq!(
+            unknown_settings(&schema(), &settings),
+            vec![
+                "formatingProvider",
+                "plugin.hlint.diagnosticOn",
+                "plugin.hlitn",
+            ]
+        );
+    }
+}
@@ -0,0 +1,277 @@
+/// Settings of the VSCode Haskell extension itself, which HLS doesn't read.
+const CLIENT_SETTINGS: &[&str] = &[
+    "ghcupExecutablePath",
+    "logFile",
+    "manageHLS",
+    "metadataURL",
+    "openDocumentationInHackage",
+    "openSourceInHackage",
+    "promptBeforeDownloads",
+    "releasesDownloadStoragePath",
+    "releasesURL",
+    "serverEnvironment",
+    "serverExecutablePath",
+    "serverExtraArgs",
+    "supportCabalFiles",
+    "toolchain",
+    "trace",
+    "upgradeGHCup",
+];
+
+/// The `haskell.*` entries of a VSCode `settings.json`, converted for Zed.
+#[derive(Debug, PartialEq)]
+pub struct Imported {
+    /// The nested settings object HLS expects.
+    pub haskell: serde_json::Value,
+    /// The `lsp.hls.binary` settings equivalent to the VSCode extension's
+    /// own `serverExecutablePath`, `serverExtraArgs`, `logFile` and
+    /// `serverEnvironment`.
+    pub binary: serde_json::Map<String, serde_json::Value>,
+    /// Settings of the VSCode extension that have no equivalent in Zed.
+    pub dropped: Vec<String>,
+}
+
+/// Converts the `haskell.*` entries of a VSCode `settings.json` into the
+/// nested settings object HLS expects in Zed, and the binary settings.
+pub fn convert(contents: &str) -> Result<Imported, String> {
+    let settings: serde_json::Value = serde_json::from_str(&strip_jsonc(contents))
+        .map_err(|e| format!("failed to parse VSCode settings: {e}"))?;
+    let settings = settings
+        .as_object()
+        .ok_or("VSCode settings must be a JSON object")?;
+
+    let mut haskell = serde_json::Map::new();
+    let mut client = serde_json::Map::new();
+    for (key, value) in settings {
+        let Some(key) = key.strip_prefix("haskell.") else {
+            continue;
+        };
+        let path: Vec<&str> = key.split('.').collect();
+        if CLIENT_SETTINGS.contains(&path[0]) {
+            client.insert(key.to_string(), value.clone());
+        } else {
+            insert_nested_value(&mut haskell, &path, value.clone());
+        }
+    }
+
+    let mut binary = serde_json::Map::new();
+    // VSCode passes `--lsp` to a configured server, and the other
+    // arguments only along with it.
+    if let Some(path) = client.remove("serverExecutablePath") {
+        let mut arguments = vec!["--lsp".to_string()];
+        if let Some(extra) = client.remove("serverExtraArgs") {
+            arguments.extend(
+                extra
+                    .as_str()
+                    .unwrap_or_default()
+                    .split_whitespace()
+                    .map(str::to_string),
+            );
+        }
+        if let Some(log_file) = client.remove("logFile") {
+            arguments.extend([
+                "--logfile".to_string(),
+                log_file.as_str().unwrap_or_default().to_string(),
+            ]);
+        }
+        binary.insert("path".to_string(), path);
+        binary.insert("arguments".to_string(), arguments.into());
+    }
+    if let Some(env) = client.remove("serverEnvironment") {
+        binary.insert("env".to_string(), env);
+    }
+    let dropped = client.keys().map(|key| format!("haskell.{key}")).collect();
+
+    Ok(Imported {
+        haskell: serde_json::Value::Object(haskell),
+        binary,
+        dropped,
+    })
+}
+
+/// Expands flat dotted keys, like `"plugin.hlint.globalOn"` as written in
+/// VSCode, into the nested objects HLS expects. Both forms can be mixed;
+/// where they set the same value, the dotted key wins.
+pub fn expand_dotted_keys(settings: &mut serde_json::Value) {
+    let Some(object) = settings.as_object_mut() else {
+        return;
+    };
+    let mut nested = serde_json::Map::new();
+    let mut dotted = serde_json::Map::new();
+    for (key, mut value) in std::mem::take(object) {
+        expand_dotted_keys(&mut value);
+        if key.contains('.') {
+            let path: Vec<&str> = key.split('.').collect();
+            insert_nested_value(&mut dotted, &path, value);
+        } else {
+            nested.insert(key, value);
+        }
+    }
+    *settings = serde_json::Value::Object(nested);
+    crate::merge_json(settings, serde_json::Value::Object(dotted));
+}
+
+/// Inserts `value` at the dotted `path`, creating intermediate objects.
+pub fn insert_nested_value(
+    object: &mut serde_json::Map<String, serde_json::Value>,
+    path: &[&str],
+    value: serde_json::Value,
+) {
+    let Some((key, rest)) = path.split_first() else {
+        return;
+    };
+    if rest.is_empty() {
+        object.insert(key.to_string(), value);
+        return;
+    }
+    let entry = object
+        .entry(key.to_string())
+        .or_insert_with(|| serde_json::json!({}));
+    if !entry.is_object() {
+        *entry = serde_json::json!({});
+    }
+    if let Some(child) = entry.as_object_mut() {
+        insert_nested_value(child, rest, value);
+    }
+}
+
+/// VSCode settings files are JSONC: strip comments and trailing commas so
+/// they can be read as plain JSON.
+fn strip_jsonc(contents: &str) -> String {
+    let mut output = String::with_capacity(contents.len());
+    let mut chars = contents.chars().peekable();
+    let mut in_string = false;
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            output.push(c);
+            match c {
+                '\\' => output.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => {
+                in_string = true;
+                output.push(c);
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                if let Some(newline) = chars.find(|&c| c == '\n') {
+                    output.push(newline);
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = None;
+                for c in chars.by_ref() {
+                    if previous == Some('*') && c == '/' {
+                        break;
+                    }
+                    previous = Some(c);
+                }
+            }
+            ',' => {
+                let rest = chars.clone().find(|c| !c.is_whitespace());
+                if !matches!(rest, Some('}' | ']')) {
+                    output.push(c);
+                }
+            }
+            _ => output.push(c),
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert_vscode_settings() {
+        let contents = r#"{
+            // Formatting
+            "haskell.formattingProvider": "fourmolu",
+            "haskell.plugin.hlint.globalOn": false, /* too noisy */
+            "haskell.plugin.eval.config.diff": true,
+            "haskell.manageHLS": "GHCup",
+            "haskell.trace.server": "messages",
+            "haskell.serverEnvironment": { "STACK_YAML": "stack-9.8.yaml" },
+            "editor.formatOnSave": true,
+            "files.exclude": { "**/dist-newstyle": true, },
+        }"#;
+
+        let imported = convert(contents).unwrap();
+        assert_eq!(
+            imported.haskell,
+            serde_json::json!({
+                "formattingProvider": "fourmolu",
+                "plugin": {
+                    "hlint": { "globalOn": false },
+                    "eval": { "config": { "diff": true } }
+                }
+            })
+        );
+        assert_eq!(
+            serde_json::Value::Object(imported.binary),
+            serde_json::json!({ "env": { "STACK_YAML": "stack-9.8.yaml" } })
+        );
+        assert_eq!(
+            imported.dropped,
+            vec!["haskell.manageHLS", "haskell.trace.server"]
+        );
+    }
+
+    #[test]
+    fn test_server_settings_become_binary_settings() {
+        let contents = r#"{
+            "haskell.serverExecutablePath": "C:\\ghcup\\bin // not a comment",
+            "haskell.serverExtraArgs": "-j4 --debug",
+            "haskell.logFile": "hls.log",
+        }"#;
+
+        let imported = convert(contents).unwrap();
+        assert_eq!(imported.haskell, serde_json::json!({}));
+        assert_eq!(
+            serde_json::Value::Object(imported.binary),
+            serde_json::json!({
+                "path": "C:\\ghcup\\bin // not a comment",
+                "arguments": ["--lsp", "-j4", "--debug", "--logfile", "hls.log"]
+            })
+        );
+        assert!(imported.dropped.is_empty());
+
+        let imported = convert(r#"{ "haskell.serverExtraArgs": "-j4" }"#).unwrap();
+        assert!(imported.binary.is_empty());
+        assert_eq!(imported.dropped, vec!["haskell.serverExtraArgs"]);
+    }
+
+    #[test]
+    fn test_expand_dotted_keys() {
+        let mut settings = serde_json::json!({
+            "formattingProvider": "fourmolu",
+            "plugin.hlint.globalOn": false,
+            "plugin.hlint.codeActionsOn": true,
+            "plugin": {
+                "hlint": { "globalOn": true, "diagnosticsOn": false },
+                "stan.globalOn": true
+            }
+        });
+        expand_dotted_keys(&mut settings);
+        assert_eq!(
+            settings,
+            serde_json::json!({
+                "formattingProvider": "fourmolu",
+                "plugin": {
+                    "hlint": {
+                        "codeActionsOn": true,
+                        "diagnosticsOn": false,
+                        "globalOn": false
+                    },
+                    "stan": { "globalOn": true }
+                }
+            })
+        );
+    }
+}
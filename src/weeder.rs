@@ -0,0 +1,119 @@
+use zed_extension_api::process::Command;
+use zed_extension_api::{self as zed, Result};
+
+/// A declaration weeder found to be unused.
+#[derive(Debug, PartialEq)]
+pub struct Weed {
+    pub path: String,
+    pub line: u32,
+    pub column: u32,
+    pub declaration: String,
+}
+
+/// Runs weeder on the worktree, with its `weeder.toml` if there is one,
+/// returning the unused declarations it found.
+///
+/// weeder reads the `.hie` files of the last build, which GHC only writes
+/// with `-fwrite-ide-info`.
+pub fn run(worktree: &zed::Worktree) -> Result<Vec<Weed>> {
+    if worktree.which("weeder").is_none() {
+        return Err("weeder is not installed, see https://github.com/ocharles/weeder".to_string());
+    }
+    // Commands can't be given a working directory, so point weeder at the
+    // worktree instead.
+    let root_path = worktree.root_path();
+    let mut args = vec!["--hie-directory".to_string(), root_path.clone()];
+    if worktree.read_text_file("weeder.toml").is_ok() {
+        args.extend(["--config".to_string(), format!("{root_path}/weeder.toml")]);
+    }
+    let output = Command::new("weeder")
+        .args(args)
+        .envs(worktree.shell_env())
+        .output()?;
+    let weeds = parse(&String::from_utf8_lossy(&output.stdout));
+    // weeder exits with an error when it finds weeds.
+    if output.status != Some(0) && weeds.is_empty() {
+        return Err(format!(
+            "weeder failed: {}{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    Ok(weeds)
+}
+
+/// Parses weeder's `path:line:column: declaration` lines.
+pub fn parse(output: &str) -> Vec<Weed> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(4, ':');
+            let path = parts.next()?.trim();
+            let line = parts.next()?.parse().ok()?;
+            let column = parts.next()?.parse().ok()?;
+            let declaration = parts.next()?.trim();
+            Some(Weed {
+                path: path.to_string(),
+                line,
+                column,
+                declaration: declaration.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Renders weeds as a Markdown list per file.
+pub fn report(weeds: &[Weed]) -> String {
+    if weeds.is_empty() {
+        return "weeder found no unused declarations.\n".to_string();
+    }
+    let mut report = String::new();
+    let mut current_path = None;
+    for weed in weeds {
+        if current_path != Some(&weed.path) {
+            if current_path.is_some() {
+                report.push('\n');
+            }
+            report.push_str(&format!("{}:\n", weed.path));
+            current_path = Some(&weed.path);
+        }
+        report.push_str(&format!(
+            "- `{}` at {}:{}:{}\n",
+            weed.declaration, weed.path, weed.line, weed.column
+        ));
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_report() {
+        let weeds = parse(
+            "src/Demo/Parser.hs:12:1: parseLegacy\n\
+             src/Demo/Parser.hs:40:1: (Instance) :: Show Token\n\
+             app/Main.hs:7:1: helper\n\
+             Weeds detected: 3\n",
+        );
+        assert_eq!(
+            weeds[1],
+            Weed {
+                path: "src/Demo/Parser.hs".to_string(),
+                line: 40,
+                column: 1,
+                declaration: "(Instance) :: Show Token".to_string(),
+            }
+        );
+        assert_eq!(
+            report(&weeds),
+            "src/Demo/Parser.hs:\n\
+             - `parseLegacy` at src/Demo/Parser.hs:12:1\n\
+             - `(Instance) :: Show Token` at src/Demo/Parser.hs:40:1\n\
+             \n\
+             app/Main.hs:\n\
+             - `helper` at app/Main.hs:7:1\n"
+        );
+    }
+}
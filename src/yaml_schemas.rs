@@ -0,0 +1,51 @@
+use std::fs;
+
+/// JSON schemas for YAML files in Haskell projects, as the file they are
+/// written to, their contents, and the files they apply to.
+const SCHEMAS: &[(&str, &str, &[&str])] = &[
+    (
+        "hpack-schema.json",
+        include_str!("hpack-schema.json"),
+        &["package.yaml"],
+    ),
+    (
+        "stack-schema.json",
+        include_str!("stack-schema.json"),
+        &["stack.yaml", "stack-*.yaml"],
+    ),
+    (
+        "stack-lock-schema.json",
+        include_str!("stack-lock-schema.json"),
+        &["stack.yaml.lock", "stack-*.yaml.lock"],
+    ),
+];
+
+/// Writes the bundled schemas to the work directory, returning the
+/// `yaml.schemas` setting of yaml-language-server that associates them with
+/// their files.
+pub fn install() -> Result<serde_json::Value, String> {
+    let work_dir = std::env::current_dir().map_err(|e| e.to_string())?;
+    let mut associations = serde_json::Map::new();
+    for (file, contents, globs) in SCHEMAS {
+        fs::write(file, contents).map_err(|e| format!("failed to write {file}: {e}"))?;
+        associations.insert(
+            work_dir.join(file).to_string_lossy().into_owned(),
+            serde_json::json!(globs),
+        );
+    }
+    Ok(serde_json::Value::Object(associations))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_schemas_are_valid_json() {
+        for (file, contents, _) in SCHEMAS {
+            let schema: serde_json::Value = serde_json::from_str(contents)
+                .unwrap_or_else(|e| panic!("{file} is not valid JSON: {e}"));
+            assert!(schema["$schema"].is_string(), "{file} has no $schema");
+        }
+    }
+}